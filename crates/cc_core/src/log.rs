@@ -1,8 +1,15 @@
 #[derive(Default, Debug)]
 pub enum LogType {
     Upload,
+    Download,
     Copy,
     Delete,
+    Meta,
+    Tag,
+    StorageClass,
+    Restore,
+    Version,
+    Link,
     #[default]
     Unkown,
 }
@@ -39,6 +46,13 @@ impl LogItem {
         }
     }
 
+    pub fn download() -> Self {
+        Self {
+            log_type: LogType::Download,
+            ..Default::default()
+        }
+    }
+
     pub fn copy() -> Self {
         Self {
             log_type: LogType::Copy,
@@ -53,6 +67,48 @@ impl LogItem {
         }
     }
 
+    pub fn meta() -> Self {
+        Self {
+            log_type: LogType::Meta,
+            ..Default::default()
+        }
+    }
+
+    pub fn tag() -> Self {
+        Self {
+            log_type: LogType::Tag,
+            ..Default::default()
+        }
+    }
+
+    pub fn storage_class() -> Self {
+        Self {
+            log_type: LogType::StorageClass,
+            ..Default::default()
+        }
+    }
+
+    pub fn restore() -> Self {
+        Self {
+            log_type: LogType::Restore,
+            ..Default::default()
+        }
+    }
+
+    pub fn version() -> Self {
+        Self {
+            log_type: LogType::Version,
+            ..Default::default()
+        }
+    }
+
+    pub fn link() -> Self {
+        Self {
+            log_type: LogType::Link,
+            ..Default::default()
+        }
+    }
+
     pub fn unknow() -> Self {
         Self {
             log_type: LogType::Unkown,
@@ -61,21 +117,25 @@ impl LogItem {
     }
 
     pub fn with_success(mut self, data: String) -> Self {
+        self.state = LogState::Success;
         self.data = data;
         self
     }
 
     pub fn with_error(mut self, data: String) -> Self {
+        self.state = LogState::Error;
         self.data = data;
         self
     }
 
     pub fn with_info(mut self, data: String) -> Self {
+        self.state = LogState::Info;
         self.data = data;
         self
     }
 
     pub fn with_warn(mut self, data: String) -> Self {
+        self.state = LogState::Warn;
         self.data = data;
         self
     }
@@ -40,6 +40,10 @@ pub struct Session {
     pub endpoint: String,
     pub bucket: String,
     pub note: String,
+    /// Base URL of public links, a custom domain or CDN in front of the
+    /// bucket.
+    #[serde(default)]
+    pub cdn_url: String,
 }
 
 impl Debug for Session {
@@ -50,6 +54,7 @@ impl Debug for Session {
             .field("endpoint", &self.endpoint)
             .field("bucket", &self.bucket)
             .field("note", &self.note)
+            .field("cdn_url", &self.cdn_url)
             .finish()
     }
 }
@@ -1,15 +1,26 @@
 use crate::store;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 fn default_page_limit() -> u16 {
     40
 }
 
+fn default_part_size() -> usize {
+    8 * 1024 * 1024
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
 #[derive(Clone, Debug, Copy, PartialEq, Default, serde::Deserialize, serde::Serialize)]
 pub enum ShowType {
     #[default]
     List,
     Thumb,
+    /// Every object below the current folder, with its relative path.
+    Flat,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -21,6 +32,16 @@ pub struct Setting {
     pub show_type: ShowType,
     /// Auto login
     pub auto_login: bool,
+    /// Multipart upload part size in bytes.
+    #[serde(default = "default_part_size")]
+    pub part_size: usize,
+    /// Number of parts uploaded concurrently.
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Content-Type of uploads by file extension, overriding the built-in
+    /// detection.
+    #[serde(default)]
+    pub content_types: BTreeMap<String, String>,
 }
 
 impl Default for Setting {
@@ -29,6 +50,9 @@ impl Default for Setting {
             page_limit: 40,
             show_type: ShowType::default(),
             auto_login: true,
+            part_size: default_part_size(),
+            concurrency: default_concurrency(),
+            content_types: BTreeMap::new(),
         }
     }
 }
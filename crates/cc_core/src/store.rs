@@ -61,7 +61,23 @@ pub fn delete_latest_session() {
     let _ = std::fs::remove_file(STORE.latest_session_file());
 }
 
+pub fn get_checkpoint<T: DeserializeOwned>(id: &str) -> Option<T> {
+    let raw = std::fs::read(STORE.checkpoint_path(id)).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+pub fn put_checkpoint<T: Serialize>(id: &str, val: &T) -> Result<(), CoreError> {
+    let raw = serde_json::to_vec_pretty(val).map_err(|err| CoreError::Custom(err.to_string()))?;
+    std::fs::write(STORE.checkpoint_path(id), raw)?;
+    Ok(())
+}
+
+pub fn delete_checkpoint(id: &str) {
+    let _ = std::fs::remove_file(STORE.checkpoint_path(id));
+}
+
 pub const SESSIONS_DIR_NAME: &str = "sessions";
+pub const CHECKPOINTS_DIR_NAME: &str = "checkpoints";
 pub const LOG_FILENAME: &str = "log";
 pub const CONTENT_DIR_NAME: &str = "content";
 pub const CONFIG_DIR_NAME: &str = "config";
@@ -70,6 +86,7 @@ pub const CONFIG_DIR_NAME: &str = "config";
 pub struct ContentStore {
     latest_session_file: PathBuf,
     sessions_dir: PathBuf,
+    checkpoints_dir: PathBuf,
     log_file: PathBuf,
     content_dir: PathBuf,
     config_dir: PathBuf,
@@ -77,10 +94,11 @@ pub struct ContentStore {
 
 impl Default for ContentStore {
     fn default() -> Self {
-        let (sessions_dir, log_file, content_dir, config_dir) =
+        let (sessions_dir, checkpoints_dir, log_file, content_dir, config_dir) =
             match directories_next::ProjectDirs::from("com", "fireyy", "caricare") {
                 Some(app_dirs) => (
                     app_dirs.data_dir().join(SESSIONS_DIR_NAME),
+                    app_dirs.data_dir().join(CHECKPOINTS_DIR_NAME),
                     app_dirs.data_dir().join(LOG_FILENAME),
                     app_dirs.cache_dir().join(CONTENT_DIR_NAME),
                     app_dirs.config_dir().to_path_buf(),
@@ -88,6 +106,7 @@ impl Default for ContentStore {
                 // Fallback to current working directory if no HOME is present
                 None => (
                     SESSIONS_DIR_NAME.into(),
+                    CHECKPOINTS_DIR_NAME.into(),
                     LOG_FILENAME.into(),
                     CONTENT_DIR_NAME.into(),
                     CONFIG_DIR_NAME.into(),
@@ -97,6 +116,7 @@ impl Default for ContentStore {
         Self {
             latest_session_file: sessions_dir.join("latest"),
             sessions_dir,
+            checkpoints_dir,
             log_file,
             content_dir,
             config_dir,
@@ -114,11 +134,17 @@ impl ContentStore {
         self.content_path(id).exists()
     }
 
+    pub fn checkpoint_path(&self, id: &str) -> PathBuf {
+        let normalized_id = urlencoding::encode(id);
+        self.checkpoints_dir().join(normalized_id.as_ref())
+    }
+
     pub fn create_req_dirs(&self) -> Result<(), CoreError> {
         use std::fs::create_dir_all;
 
         create_dir_all(self.content_dir())?;
         create_dir_all(self.sessions_dir())?;
+        create_dir_all(self.checkpoints_dir())?;
         create_dir_all(self.log_file().parent().unwrap_or_else(|| Path::new(".")))?;
         create_dir_all(self.config_dir())?;
 
@@ -140,6 +166,11 @@ impl ContentStore {
         self.sessions_dir.as_path()
     }
 
+    #[inline(always)]
+    pub fn checkpoints_dir(&self) -> &Path {
+        self.checkpoints_dir.as_path()
+    }
+
     #[inline(always)]
     pub fn log_file(&self) -> &Path {
         self.log_file.as_path()
@@ -0,0 +1,516 @@
+//! Signed requests for the parts of the storage REST APIs that opendal does
+//! not expose, such as multipart upload ids.
+use crate::config::ClientConfig;
+use crate::error::OSSError;
+use crate::Result;
+use anyhow::Context;
+use base64::Engine;
+use bytes::Bytes;
+use cc_core::ServiceType;
+use http::header::{HeaderName, HeaderValue, CONTENT_LENGTH};
+use http::{HeaderMap, Method, StatusCode};
+use md5::{Digest, Md5};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqsign::{
+    AliyunCredential, AliyunOssSigner, AwsConfig, AwsCredential, AwsV4Signer,
+    AzureStorageCredential, AzureStorageSigner, GoogleCredentialLoader, GoogleSigner,
+    GoogleTokenLoader,
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use std::time::Duration;
+
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+const GCS_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.full_control";
+const AZURE_VERSION: &str = "2021-08-06";
+
+/// Characters kept as is in keys and query values, see
+/// <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html>
+const KEY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// `key` as the path of an object URL.
+pub(crate) fn encode_key(key: &str) -> String {
+    utf8_percent_encode(key.trim_start_matches('/'), KEY_ENCODE_SET).to_string()
+}
+const QUERY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+enum Signer {
+    Aws(AwsV4Signer, AwsCredential),
+    Oss(AliyunOssSigner, AliyunCredential),
+    Azblob(AzureStorageSigner, AzureStorageCredential),
+    Gcs(GoogleSigner, Box<GoogleTokenLoader>),
+}
+
+struct ApiInner {
+    service: ServiceType,
+    endpoint: String,
+    bucket: String,
+    bucket_url: String,
+    /// Signing region of S3, `us-east-1` where there is none.
+    region: String,
+    http: reqwest::Client,
+    signer: Signer,
+}
+
+#[derive(Clone)]
+pub(crate) struct Api {
+    inner: Arc<ApiInner>,
+}
+
+impl Api {
+    pub(crate) fn new(config: &ClientConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .context("build http client")?;
+        let endpoint = normalize_endpoint(&config.service, &config.endpoint);
+        let region = if config.service == ServiceType::S3 {
+            detect_aws_region(&endpoint)
+        } else {
+            "us-east-1".to_string()
+        };
+
+        let (bucket_url, signer) = match &config.service {
+            ServiceType::S3 | ServiceType::S3Compatible => {
+                let virtual_host = config.service == ServiceType::S3Compatible
+                    && endpoint.contains("aliyuncs.com");
+                let url = if virtual_host {
+                    virtual_host_url(&endpoint, &config.bucket)
+                } else {
+                    format!("{endpoint}/{}", config.bucket)
+                };
+                let cred = AwsCredential {
+                    access_key_id: config.access_key_id.clone(),
+                    secret_access_key: config.access_key_secret.clone(),
+                    ..Default::default()
+                };
+                (url, Signer::Aws(AwsV4Signer::new("s3", &region), cred))
+            }
+            ServiceType::Oss => {
+                let cred = AliyunCredential {
+                    access_key_id: config.access_key_id.clone(),
+                    access_key_secret: config.access_key_secret.clone(),
+                    ..Default::default()
+                };
+                (
+                    virtual_host_url(&endpoint, &config.bucket),
+                    Signer::Oss(AliyunOssSigner::new(&config.bucket), cred),
+                )
+            }
+            ServiceType::Azblob => {
+                let cred = AzureStorageCredential::SharedKey(
+                    config.access_key_id.clone(),
+                    config.access_key_secret.clone(),
+                );
+                (
+                    format!("{endpoint}/{}", config.bucket),
+                    Signer::Azblob(AzureStorageSigner::new(), cred),
+                )
+            }
+            ServiceType::Gcs => {
+                let cred = GoogleCredentialLoader::default()
+                    .with_content(&config.access_key_secret)
+                    .with_disable_env()
+                    .with_disable_well_known_location()
+                    .load()?
+                    .context("invalid google cloud storage credential")?;
+                let loader = GoogleTokenLoader::new(GCS_SCOPE, http.clone()).with_credentials(cred);
+                (
+                    format!("{endpoint}/{}", config.bucket),
+                    Signer::Gcs(GoogleSigner::new("storage"), Box::new(loader)),
+                )
+            }
+        };
+
+        Ok(Self {
+            inner: Arc::new(ApiInner {
+                service: config.service.clone(),
+                endpoint,
+                bucket: config.bucket.clone(),
+                bucket_url,
+                region,
+                http,
+                signer,
+            }),
+        })
+    }
+
+    pub(crate) fn service(&self) -> &ServiceType {
+        &self.inner.service
+    }
+
+    pub(crate) fn bucket_url(&self) -> &str {
+        &self.inner.bucket_url
+    }
+
+    pub(crate) fn region(&self) -> &str {
+        &self.inner.region
+    }
+
+    pub(crate) fn object_url(&self, key: &str) -> String {
+        format!("{}/{}", self.inner.bucket_url, encode_key(key))
+    }
+
+    /// Server-side copy `src_key` of the bucket behind `src` to `key`, both
+    /// buckets must be readable with the credentials of this one.
+    pub(crate) async fn copy_from(&self, src: &Api, src_key: &str, key: &str) -> Result<()> {
+        self.object(Method::PUT, key)
+            .copy_source(src, src_key)?
+            .send_copy()
+            .await
+    }
+
+    /// Request on an object of the bucket.
+    pub(crate) fn object(&self, method: Method, key: &str) -> ApiRequest {
+        self.url(method, self.object_url(key))
+    }
+
+    /// Request on the bucket itself, e.g. `?acl`.
+    pub(crate) fn bucket(&self, method: Method) -> ApiRequest {
+        let url = &self.inner.bucket_url;
+        // Virtual hosted buckets need a `/` path, path style ones must not
+        // end with one on Azure.
+        if url.ends_with(&format!("/{}", self.inner.bucket)) {
+            self.url(method, url.clone())
+        } else {
+            self.url(method, format!("{url}/"))
+        }
+    }
+
+    /// Request on a path of the service endpoint, outside of the bucket.
+    pub(crate) fn endpoint(&self, method: Method, path: &str) -> ApiRequest {
+        self.url(method, format!("{}/{}", self.inner.endpoint, path))
+    }
+
+    pub(crate) fn bucket_name(&self) -> &str {
+        &self.inner.bucket
+    }
+
+    fn url(&self, method: Method, url: String) -> ApiRequest {
+        ApiRequest {
+            api: self.clone(),
+            method,
+            url,
+            query: vec![],
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    async fn sign(&self, req: &mut http::Request<Bytes>) -> Result<()> {
+        match &self.inner.signer {
+            Signer::Aws(signer, cred) => {
+                req.headers_mut().insert(
+                    "x-amz-content-sha256",
+                    HeaderValue::from_static("UNSIGNED-PAYLOAD"),
+                );
+                signer.sign(req, cred)?
+            }
+            Signer::Oss(signer, cred) => signer.sign(req, cred)?,
+            Signer::Azblob(signer, cred) => {
+                req.headers_mut()
+                    .insert("x-ms-version", HeaderValue::from_static(AZURE_VERSION));
+                let len = req.body().len();
+                if len > 0 {
+                    req.headers_mut()
+                        .insert(CONTENT_LENGTH, HeaderValue::from(len));
+                }
+                signer.sign(req, cred)?
+            }
+            Signer::Gcs(signer, loader) => {
+                let token = loader
+                    .load()
+                    .await?
+                    .context("no token for google cloud storage")?;
+                signer.sign(req, &token)?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct ApiRequest {
+    api: Api,
+    method: Method,
+    url: String,
+    query: Vec<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl ApiRequest {
+    /// Add a query parameter, an empty value is sent as a bare key (`?uploads`).
+    pub(crate) fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn header(mut self, key: &str, value: &str) -> Result<Self> {
+        self.headers
+            .insert(HeaderName::try_from(key)?, HeaderValue::try_from(value)?);
+        Ok(self)
+    }
+
+    /// Copy the content from `src_key` of the bucket behind `src`.
+    pub(crate) fn copy_source(self, src: &Api, src_key: &str) -> Result<Self> {
+        let source = format!(
+            "/{}/{}",
+            src.inner.bucket,
+            utf8_percent_encode(src_key.trim_start_matches('/'), KEY_ENCODE_SET)
+        );
+        match self.api.service() {
+            ServiceType::S3 | ServiceType::S3Compatible => {
+                self.header("x-amz-copy-source", &source)
+            }
+            ServiceType::Oss => self.header("x-oss-copy-source", &source),
+            ServiceType::Gcs => self.header("x-goog-copy-source", &source),
+            ServiceType::Azblob => self.header("x-ms-copy-source", &src.object_url(src_key)),
+        }
+    }
+
+    /// Copy the version `version_id` of `src_key`, as listed by
+    /// `versions::list`.
+    pub(crate) fn copy_source_version(
+        self,
+        src: &Api,
+        src_key: &str,
+        version_id: &str,
+    ) -> Result<Self> {
+        let id = utf8_percent_encode(version_id, QUERY_ENCODE_SET).to_string();
+        match self.api.service() {
+            ServiceType::Gcs => self
+                .copy_source(src, src_key)?
+                .header("x-goog-copy-source-generation", version_id),
+            ServiceType::Azblob => self.header(
+                "x-ms-copy-source",
+                &format!("{}?versionid={id}", src.object_url(src_key)),
+            ),
+            _ => {
+                let source = format!(
+                    "/{}/{}?versionId={id}",
+                    src.inner.bucket,
+                    utf8_percent_encode(src_key.trim_start_matches('/'), KEY_ENCODE_SET)
+                );
+                let header = match self.api.service() {
+                    ServiceType::Oss => "x-oss-copy-source",
+                    _ => "x-amz-copy-source",
+                };
+                self.header(header, &source)
+            }
+        }
+    }
+
+    /// Send a request built with `copy_source`.
+    pub(crate) async fn send_copy(self) -> Result<()> {
+        let resp = self.send().await?;
+        // S3 may report a failed copy with a 200 status.
+        if resp.body.windows(7).any(|w| w == b"<Error>") {
+            let text = String::from_utf8_lossy(&resp.body).to_string();
+            return Err(OSSError::WithDescription(text).into());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// An XML body, with the `Content-MD5` that S3 and OSS require for some
+    /// requests.
+    pub(crate) fn xml_body(self, body: String) -> Result<Self> {
+        let md5 = base64::engine::general_purpose::STANDARD.encode(Md5::digest(body.as_bytes()));
+        Ok(self.header("content-md5", &md5)?.body(body))
+    }
+
+    fn uri(&self) -> String {
+        let mut uri = self.url.clone();
+        for (i, (k, v)) in self.query.iter().enumerate() {
+            uri.push(if i == 0 { '?' } else { '&' });
+            uri.push_str(&utf8_percent_encode(k, QUERY_ENCODE_SET).to_string());
+            if !v.is_empty() {
+                uri.push('=');
+                uri.push_str(&utf8_percent_encode(v, QUERY_ENCODE_SET).to_string());
+            }
+        }
+        uri
+    }
+
+    /// A URL anyone can send this request to until `expire`, with every query
+    /// parameter signed. Only S3 and OSS sign arbitrary parameters.
+    pub(crate) fn presign(self, expire: Duration) -> Result<String> {
+        let mut req = http::Request::builder()
+            .method(self.method.clone())
+            .uri(self.uri())
+            .body(Bytes::new())?;
+        match &self.api.inner.signer {
+            Signer::Aws(signer, cred) => signer.sign_query(&mut req, expire, cred)?,
+            Signer::Oss(signer, cred) => signer.sign_query(&mut req, expire, cred)?,
+            _ => {
+                return Err(OSSError::WithDescription(
+                    "Custom query parameters are only supported by S3 and OSS".into(),
+                )
+                .into())
+            }
+        }
+
+        Ok(req.uri().to_string())
+    }
+
+    pub(crate) async fn send(self) -> Result<ApiResponse> {
+        let resp = self.execute().await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        Ok(ApiResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Send the request and leave the body of a successful response unread,
+    /// to stream large objects.
+    pub(crate) async fn execute(self) -> Result<reqwest::Response> {
+        let mut req = http::Request::builder()
+            .method(self.method.clone())
+            .uri(self.uri())
+            .body(self.body.clone())?;
+        *req.headers_mut() = self.headers.clone();
+        self.api.sign(&mut req).await?;
+
+        tracing::debug!("Api request: {} {}", req.method(), req.uri());
+
+        let resp = self
+            .api
+            .inner
+            .http
+            .execute(reqwest::Request::try_from(req)?)
+            .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let resp = ApiResponse {
+                status,
+                headers: resp.headers().clone(),
+                body: resp.bytes().await?,
+            };
+            return Err(resp.into_error().into());
+        }
+
+        Ok(resp)
+    }
+}
+
+pub(crate) struct ApiResponse {
+    pub(crate) status: StatusCode,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Bytes,
+}
+
+impl ApiResponse {
+    pub(crate) fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    pub(crate) fn xml<T: DeserializeOwned>(&self) -> Result<T> {
+        let text = std::str::from_utf8(&self.body).context("response is not utf-8")?;
+        quick_xml::de::from_str(text).context("parse xml response")
+    }
+
+    pub(crate) fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body).context("parse json response")
+    }
+
+    fn into_error(self) -> OSSError {
+        #[derive(serde::Deserialize, Default)]
+        #[serde(rename_all = "PascalCase", default)]
+        struct ErrorBody {
+            code: String,
+            message: String,
+            request_id: String,
+        }
+
+        let request_id = ["x-amz-request-id", "x-oss-request-id", "x-ms-request-id"]
+            .iter()
+            .find_map(|k| self.header(k))
+            .unwrap_or_default()
+            .to_string();
+        let text = String::from_utf8_lossy(&self.body).to_string();
+        let err = quick_xml::de::from_str::<ErrorBody>(&text).unwrap_or_else(|_| ErrorBody {
+            code: self.status.canonical_reason().unwrap_or_default().into(),
+            message: text,
+            ..Default::default()
+        });
+
+        OSSError::ServiceError(
+            self.status.as_u16(),
+            err.code,
+            err.message,
+            if err.request_id.is_empty() {
+                request_id
+            } else {
+                err.request_id
+            },
+        )
+    }
+}
+
+fn normalize_endpoint(service: &ServiceType, endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.is_empty() && *service == ServiceType::Gcs {
+        GCS_ENDPOINT.to_string()
+    } else if endpoint.contains("://") {
+        endpoint.to_string()
+    } else {
+        format!("https://{endpoint}")
+    }
+}
+
+fn virtual_host_url(endpoint: &str, bucket: &str) -> String {
+    match endpoint.split_once("://") {
+        Some((scheme, host)) => format!("{scheme}://{bucket}.{host}"),
+        None => format!("https://{bucket}.{endpoint}"),
+    }
+}
+
+/// The region the S3 operator of opendal signs with: `AWS_REGION` or the
+/// `region` of the AWS profile, then the region of endpoints like
+/// `https://s3.us-west-2.amazonaws.com`. The global endpoint signs for
+/// `us-east-1`, as the other endpoints do with a warning.
+fn detect_aws_region(endpoint: &str) -> String {
+    if let Some(region) = AwsConfig::default()
+        .from_profile()
+        .from_env()
+        .region
+        .filter(|r| !r.is_empty())
+    {
+        return region;
+    }
+    let host = endpoint
+        .split_once("://")
+        .map(|(_, host)| host)
+        .unwrap_or(endpoint);
+    host.split('.')
+        .nth(1)
+        .filter(|_| host.ends_with("amazonaws.com") || host.ends_with("amazonaws.com.cn"))
+        .filter(|region| *region != "amazonaws")
+        .unwrap_or_else(|| {
+            if host.trim_end_matches('/') != "s3.amazonaws.com" {
+                tracing::warn!("No region for {endpoint}, set AWS_REGION, signing for us-east-1");
+            }
+            "us-east-1"
+        })
+        .to_string()
+}
@@ -0,0 +1,337 @@
+//! Access settings, region, creation date and versioning of the bucket, read
+//! with the bucket level REST APIs of each service.
+use crate::api::Api;
+use crate::error::OSSError;
+use crate::types::{Bucket, BucketACL, Versioning};
+use crate::Result;
+use cc_core::ServiceType;
+use chrono::{DateTime, Utc};
+use http::Method;
+use serde::Deserialize;
+
+const ALL_USERS: &str = "http://acs.amazonaws.com/groups/global/AllUsers";
+
+/// Read what the credentials allow, a denied detail is left unknown and
+/// reported in `Bucket::warnings` instead of failing the whole request.
+pub(crate) async fn bucket_info(api: &Api) -> Result<Bucket> {
+    let mut bucket = Bucket::new(api.bucket_name().to_string(), BucketACL::Private);
+    match api.service() {
+        ServiceType::S3 | ServiceType::S3Compatible => s3_info(api, &mut bucket).await,
+        ServiceType::Oss => oss_info(api, &mut bucket).await,
+        ServiceType::Gcs => gcs_info(api, &mut bucket).await,
+        ServiceType::Azblob => azblob_info(api, &mut bucket).await,
+    }
+
+    Ok(bucket)
+}
+
+fn warn<T>(bucket: &mut Bucket, what: &str, result: Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            tracing::warn!("bucket {what}: {err}");
+            bucket.warnings.push(format!("{what}: {err}"));
+            None
+        }
+    }
+}
+
+fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<OSSError>(),
+        Some(OSSError::ServiceError(404, ..))
+    )
+}
+
+async fn s3_info(api: &Api, bucket: &mut Bucket) {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct AccessControlPolicy {
+        access_control_list: AccessControlList,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct AccessControlList {
+        #[serde(rename = "Grant")]
+        grants: Vec<Grant>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Grant {
+        grantee: Grantee,
+        permission: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Grantee {
+        #[serde(rename = "URI")]
+        uri: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct PolicyStatus {
+        is_public: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct LocationConstraint {
+        #[serde(rename = "$text")]
+        region: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct VersioningConfiguration {
+        status: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct ListAllMyBucketsResult {
+        buckets: Buckets,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Buckets {
+        #[serde(rename = "Bucket")]
+        buckets: Vec<BucketEntry>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct BucketEntry {
+        name: String,
+        creation_date: String,
+    }
+
+    let acl = async {
+        api.bucket(Method::GET)
+            .query("acl", "")
+            .send()
+            .await?
+            .xml::<AccessControlPolicy>()
+    };
+    if let Some(acl) = warn(bucket, "acl", acl.await) {
+        let public = |perm: &str| {
+            acl.access_control_list
+                .grants
+                .iter()
+                .any(|g| g.grantee.uri == ALL_USERS && (g.permission == perm))
+        };
+        if public("FULL_CONTROL") || public("WRITE") {
+            bucket.set_grant(BucketACL::PublicReadWrite);
+        } else if public("READ") {
+            bucket.set_grant(BucketACL::PublicRead);
+        }
+    }
+
+    // A bucket policy can make objects public even with a private acl.
+    let status = async {
+        match api
+            .bucket(Method::GET)
+            .query("policyStatus", "")
+            .send()
+            .await
+        {
+            Ok(resp) => Ok(resp.xml::<PolicyStatus>()?.is_public),
+            // No bucket policy at all.
+            Err(err) if is_not_found(&err) => Ok(String::new()),
+            Err(err) => Err(err),
+        }
+    };
+    if let Some(is_public) = warn(bucket, "policy status", status.await) {
+        if is_public.eq_ignore_ascii_case("true") && bucket.is_private() {
+            bucket.set_grant(BucketACL::PublicRead);
+        }
+    }
+
+    let location = async {
+        api.bucket(Method::GET)
+            .query("location", "")
+            .send()
+            .await?
+            .xml::<LocationConstraint>()
+    };
+    if let Some(location) = warn(bucket, "location", location.await) {
+        // Buckets of the first region report an empty location.
+        bucket.region = Some(if location.region.is_empty() {
+            "us-east-1".to_string()
+        } else {
+            location.region
+        });
+    }
+
+    let versioning = async {
+        api.bucket(Method::GET)
+            .query("versioning", "")
+            .send()
+            .await?
+            .xml::<VersioningConfiguration>()
+    };
+    if let Some(versioning) = warn(bucket, "versioning", versioning.await) {
+        bucket.versioning = match versioning.status.as_str() {
+            "Enabled" => Versioning::Enabled,
+            "Suspended" => Versioning::Suspended,
+            _ => Versioning::Disabled,
+        };
+    }
+
+    // The creation date is only part of the bucket list.
+    let list = async {
+        api.endpoint(Method::GET, "")
+            .send()
+            .await?
+            .xml::<ListAllMyBucketsResult>()
+    };
+    if let Some(list) = warn(bucket, "creation date", list.await) {
+        bucket.creation_date = list
+            .buckets
+            .buckets
+            .iter()
+            .find(|b| b.name == api.bucket_name())
+            .and_then(|b| parse_date(&b.creation_date));
+    }
+}
+
+async fn oss_info(api: &Api, bucket: &mut Bucket) {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct BucketInfo {
+        bucket: Info,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Info {
+        creation_date: String,
+        location: String,
+        access_control_list: AccessControlList,
+        versioning: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct AccessControlList {
+        grant: String,
+    }
+
+    // Everything is part of a single response on OSS.
+    let info = async {
+        api.bucket(Method::GET)
+            .query("bucketInfo", "")
+            .send()
+            .await?
+            .xml::<BucketInfo>()
+    };
+    let Some(BucketInfo { bucket: info }) = warn(bucket, "info", info.await) else {
+        return;
+    };
+    bucket.set_grant(Bucket::get_acl_from_str(&info.access_control_list.grant));
+    bucket.region = Some(info.location).filter(|r| !r.is_empty());
+    bucket.creation_date = parse_date(&info.creation_date);
+    bucket.versioning = match info.versioning.as_str() {
+        "Enabled" => Versioning::Enabled,
+        "Suspended" => Versioning::Suspended,
+        _ => Versioning::Disabled,
+    };
+}
+
+async fn gcs_info(api: &Api, bucket: &mut Bucket) {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "camelCase", default)]
+    struct Metadata {
+        location: String,
+        time_created: String,
+        versioning: Option<VersioningConfig>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct VersioningConfig {
+        enabled: bool,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Policy {
+        bindings: Vec<Binding>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Binding {
+        role: String,
+        members: Vec<String>,
+    }
+
+    let path = format!("storage/v1/b/{}", api.bucket_name());
+    let meta = async {
+        api.endpoint(Method::GET, &path)
+            .send()
+            .await?
+            .json::<Metadata>()
+    };
+    if let Some(meta) = warn(bucket, "metadata", meta.await) {
+        bucket.region = Some(meta.location).filter(|r| !r.is_empty());
+        bucket.creation_date = parse_date(&meta.time_created);
+        bucket.versioning = match meta.versioning {
+            Some(v) if v.enabled => Versioning::Enabled,
+            Some(_) => Versioning::Suspended,
+            None => Versioning::Disabled,
+        };
+    }
+
+    // Public access is granted with IAM bindings on `allUsers`.
+    let iam = async {
+        api.endpoint(Method::GET, &format!("{path}/iam"))
+            .send()
+            .await?
+            .json::<Policy>()
+    };
+    if let Some(iam) = warn(bucket, "iam policy", iam.await) {
+        let public_roles = iam
+            .bindings
+            .iter()
+            .filter(|b| {
+                b.members
+                    .iter()
+                    .any(|m| m == "allUsers" || m == "allAuthenticatedUsers")
+            })
+            .map(|b| b.role.as_str());
+        let mut grant = BucketACL::Private;
+        for role in public_roles {
+            match role {
+                "roles/storage.objectAdmin"
+                | "roles/storage.objectCreator"
+                | "roles/storage.objectUser"
+                | "roles/storage.admin"
+                | "roles/storage.legacyBucketWriter"
+                | "roles/storage.legacyBucketOwner" => grant = BucketACL::PublicReadWrite,
+                "roles/storage.objectViewer"
+                | "roles/storage.legacyObjectReader"
+                | "roles/storage.legacyBucketReader"
+                    if grant == BucketACL::Private =>
+                {
+                    grant = BucketACL::PublicRead
+                }
+                _ => {}
+            }
+        }
+        bucket.set_grant(grant);
+    }
+}
+
+async fn azblob_info(api: &Api, bucket: &mut Bucket) {
+    // Region, creation date and versioning belong to the storage account and
+    // are only exposed by the management API.
+    let props = async {
+        api.bucket(Method::GET)
+            .query("restype", "container")
+            .send()
+            .await
+    };
+    if let Some(props) = warn(bucket, "container properties", props.await) {
+        // `blob` allows anonymous reads, `container` also anonymous listing.
+        if props.header("x-ms-blob-public-access").is_some() {
+            bucket.set_grant(BucketACL::PublicRead);
+        }
+    }
+}
@@ -1,12 +1,27 @@
+use std::collections::HashSet;
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 
+use crate::api::Api;
 use crate::config::ClientConfig;
-use crate::partial_file::PartialFile;
+use crate::content_type::{self, ContentTypes};
+use crate::error::OSSError;
+use crate::link_export::SignedLinks;
+use crate::listing;
+use crate::meta::{MetaPatch, ObjectMeta};
+use crate::multipart::ResumableUpload;
+use crate::partial_file::{PartialFile, PartialMeta};
+use crate::post_policy::{self, PostPolicy, PostPolicyParams};
+use crate::restore::{self, RestoreState};
+use crate::search::{CancelToken, SearchQuery};
+use crate::storage_class;
+use crate::tagging::{self, TagPatch, Tags};
 use crate::transfer::{TransferProgressInfo, TransferSender, TransferType};
-use crate::types::{Bucket, ListObjects, ListObjectsV2Params, Object, Params};
-use crate::util::get_name;
+use crate::types::{BatchItem, Bucket, ListObjects, ListObjectsV2Params, Object, Params};
+use crate::util::{get_name, get_name_form_path, walk_dir};
+use crate::versions::{self, ObjectVersion};
 use crate::Result;
 use anyhow::Context;
 use cc_core::ServiceType;
@@ -15,13 +30,27 @@ use crate::services;
 use crate::stream::{
     AsyncReadProgressExt, BoxedStreamingUploader, StreamingUploader, TrackableBodyStream,
 };
-use futures::{AsyncReadExt, StreamExt, TryStreamExt};
+use futures::io::{AllowStdIo, AsyncWriteExt};
+use futures::{StreamExt, TryStreamExt};
 use opendal::{Metadata, Metakey, Operator};
 
+/// Number of keys scanned between two reports of a search.
+const SEARCH_BATCH_SIZE: usize = 200;
+/// S3 and most compatible services accept at most 1000 keys per batch delete.
+const DELETE_BATCH_SIZE: usize = 1000;
+/// Query parameters of signed URLs overriding headers of the response.
+const RESPONSE_OVERRIDES: [&str; 3] = [
+    "response-content-disposition",
+    "response-content-type",
+    "response-cache-control",
+];
+
 #[derive(Clone)]
 pub struct Client {
     pub(crate) config: Arc<ClientConfig>,
     operator: Operator,
+    api: Api,
+    content_types: Arc<RwLock<ContentTypes>>,
 }
 
 impl Client {
@@ -45,25 +74,40 @@ impl Client {
             // }
         };
 
-        Ok(Client { config, operator })
-    }
+        let api = Api::new(&config)?;
+        let content_types = Arc::new(RwLock::new(config.content_types.clone()));
 
-    pub fn get_bucket_url(&self) -> String {
-        let url = &self.config.endpoint;
-        let name_str = self.config.bucket.to_string();
+        Ok(Client {
+            config,
+            operator,
+            api,
+            content_types,
+        })
+    }
 
-        let mut name = String::from("https://");
-        name.push_str(&name_str);
-        name.push('.');
+    /// Replace the extension to Content-Type overrides of uploads, shared by
+    /// all clones of this client.
+    pub fn set_content_types(&self, content_types: ContentTypes) {
+        *self.content_types.write().unwrap() = content_types;
+    }
 
-        url.replace("https://", &name)
+    pub fn get_bucket_url(&self) -> String {
+        self.api.bucket_url().to_string()
     }
 
     pub async fn get_bucket_info(&self) -> Result<Bucket> {
-        //TODO: check for bucket acl
-        let grant = Bucket::get_acl_from_str("private");
+        crate::bucket::bucket_info(&self.api).await
+    }
+
+    /// Unsigned url of `key`, only readable on public buckets.
+    pub fn public_url(&self, key: &str) -> String {
+        self.api.object_url(key)
+    }
 
-        Ok(Bucket::new(self.config.bucket.to_owned(), grant))
+    /// Url of `key` on the custom domain or CDN of the session, if any.
+    pub fn cdn_url(&self, key: &str) -> Option<String> {
+        (!self.config.cname.is_empty())
+            .then(|| format!("{}/{}", self.config.cname, crate::api::encode_key(key)))
     }
 
     pub async fn meta_data(&self, object: impl AsRef<str>) -> Result<Metadata> {
@@ -107,67 +151,509 @@ impl Client {
         Ok(result)
     }
 
-    pub async fn delete_multi_object(self, obj: Vec<Object>) -> Result<bool> {
-        let mut paths: Vec<String> = vec![];
+    /// Delete `keys`, folders are deleted with everything below them.
+    ///
+    /// Keys are removed in batches, the progress is reported as a task and
+    /// the result of every key is returned.
+    pub async fn delete_multi_object(
+        &self,
+        keys: Vec<String>,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let task = match keys.as_slice() {
+            [key] => format!("Delete {key}"),
+            _ => format!("Delete {} items", keys.len()),
+        };
+        let paths = self.expand_keys(&keys).await?;
+        self.delete_paths(task, paths, &transfer).await
+    }
 
-        for o in obj.iter() {
-            paths.push(o.key().into());
+    /// Delete exactly `paths`, folders are not expanded.
+    async fn delete_paths(
+        &self,
+        task: String,
+        paths: Vec<String>,
+        transfer: &TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let total = paths.len() as u64;
+        let send_progress = |done: u64| {
+            transfer
+                .send(TransferType::Task(
+                    task.clone(),
+                    TransferProgressInfo {
+                        total_bytes: total,
+                        transferred_bytes: done,
+                    },
+                ))
+                .unwrap();
+        };
+        send_progress(0);
+
+        let mut results = Vec::with_capacity(paths.len());
+        for batch in paths.chunks(DELETE_BATCH_SIZE) {
+            match self.operator.remove(batch.to_vec()).await {
+                Ok(_) => results.extend(batch.iter().map(BatchItem::ok)),
+                // Find out which keys of the batch failed.
+                Err(_) => {
+                    for path in batch {
+                        results.push(match self.operator.delete(path).await {
+                            Ok(_) => BatchItem::ok(path),
+                            Err(err) => BatchItem::err(path, err),
+                        });
+                    }
+                }
+            }
+            send_progress(results.len() as u64);
         }
 
-        self.operator.remove(paths).await?;
-        // TODO: check delete result
-
-        Ok(true)
+        Ok(results)
     }
 
-    pub async fn list_v2(&self, query: ListObjectsV2Params) -> Result<ListObjects> {
-        tracing::debug!("List object: {:?}", query);
-        let mut path = query.prefix;
-        if !path.is_empty() && !path.ends_with('/') {
-            path.push('/');
+    /// All the headers, the user metadata and the tags of `key`.
+    pub async fn object_meta(&self, key: &str) -> Result<ObjectMeta> {
+        let mut meta = crate::meta::get(&self.api, key).await?;
+        if self.supports_tagging() {
+            match tagging::get(&self.api, key).await {
+                Ok(tags) => meta.tags = Some(tags),
+                Err(err) => tracing::warn!("Get tags of {}: {}", key, err),
+            }
         }
-        //TODO 分页功能
-        let mut stream = self
-            .operator
-            .lister_with(&path)
-            .start_after(&query.start_after)
-            .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::LastModified)
-            .await?
-            .chunks(100);
 
-        let (mut common_prefixes, mut objects) = (vec![], vec![]);
+        Ok(meta)
+    }
 
-        let page = stream.next().await.unwrap_or_default();
+    pub fn supports_tagging(&self) -> bool {
+        tagging::is_supported(&self.config.service)
+    }
 
-        let is_truncated = page.len() >= 100;
+    pub async fn get_object_tags(&self, key: &str) -> Result<Tags> {
+        tagging::get(&self.api, key).await
+    }
 
-        for v in page {
-            let entry = v?;
-            let meta = entry.metadata();
-            if meta.is_dir() {
-                common_prefixes.push(Object::new_folder(entry.path()));
+    pub async fn put_object_tags(&self, key: &str, tags: &Tags) -> Result<()> {
+        tagging::put(&self.api, key, tags).await
+    }
+
+    pub async fn delete_object_tags(&self, key: &str) -> Result<()> {
+        tagging::put(&self.api, key, &vec![]).await
+    }
+
+    /// Tags of every key, the objects whose tags can not be read get none.
+    pub async fn objects_tags(&self, keys: Vec<String>) -> Vec<(String, Tags)> {
+        let futures: Vec<_> = keys
+            .into_iter()
+            .map(|key| {
+                let api = self.api.clone();
+                async move {
+                    let tags = tagging::get(&api, &key).await.unwrap_or_else(|err| {
+                        tracing::warn!("Get tags of {}: {}", key, err);
+                        vec![]
+                    });
+                    (key, tags)
+                }
+            })
+            .collect();
+
+        futures::stream::iter(futures)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Apply `patch` to the tags of every object in `keys`, the objects below
+    /// folders included.
+    pub async fn tag_multi_object(
+        &self,
+        keys: Vec<String>,
+        patch: TagPatch,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let task = match keys.as_slice() {
+            [key] => format!("Tag {key}"),
+            _ => format!("Tag {} items", keys.len()),
+        };
+        let patch = &patch;
+        self.for_each_object(task, &keys, transfer, |api, path| async move {
+            // A replacement does not depend on the current tags.
+            let mut tags = if patch.replace {
+                vec![]
             } else {
-                objects.push(Object::new(
-                    entry.path(),
-                    meta.last_modified(),
-                    meta.content_length() as usize,
-                ));
+                tagging::get(&api, &path).await?
+            };
+            patch.apply(&mut tags);
+            tagging::put(&api, &path, &tags).await
+        })
+        .await
+    }
+
+    /// Apply `patch` to the metadata of every object in `keys`, the objects
+    /// below folders included.
+    pub async fn patch_object_meta(
+        &self,
+        keys: Vec<String>,
+        patch: MetaPatch,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let task = match keys.as_slice() {
+            [key] => format!("Metadata {key}"),
+            _ => format!("Metadata {} items", keys.len()),
+        };
+        let patch = &patch;
+        self.for_each_object(task, &keys, transfer, |api, path| async move {
+            let mut meta = crate::meta::get(&api, &path).await?;
+            meta.apply(patch);
+            crate::meta::set(&api, &path, &meta).await
+        })
+        .await
+    }
+
+    /// Classes objects can be moved to with `set_storage_class`.
+    pub fn storage_classes(&self) -> &'static [&'static str] {
+        storage_class::classes(&self.config.service)
+    }
+
+    /// Move every object in `keys` to `class`, the objects below folders
+    /// included.
+    pub async fn set_storage_class(
+        &self,
+        keys: Vec<String>,
+        class: String,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let task = match keys.as_slice() {
+            [key] => format!("{class} {key}"),
+            _ => format!("{class} {} items", keys.len()),
+        };
+        let class = &class;
+        self.for_each_object(task, &keys, transfer, |api, path| async move {
+            storage_class::set(&api, &path, class).await
+        })
+        .await
+    }
+
+    pub fn restore_tiers(&self) -> &'static [&'static str] {
+        restore::tiers(&self.config.service)
+    }
+
+    /// Retrieval tiers of a restore of objects of `class`.
+    pub fn class_restore_tiers(&self, class: &str) -> &'static [&'static str] {
+        restore::class_tiers(&self.config.service, class)
+    }
+
+    /// Objects of `class` must be restored before a read.
+    pub fn is_archive_class(&self, class: &str) -> bool {
+        restore::is_archive_class(&self.config.service, class)
+    }
+
+    pub async fn restore_status(&self, key: &str) -> Result<RestoreState> {
+        restore::status(&self.api, key).await
+    }
+
+    /// Restore every archived object in `keys` for `days` with the retrieval
+    /// `tier`, the objects below folders included. Objects that are not
+    /// archived or already being restored are skipped.
+    pub async fn restore_objects(
+        &self,
+        keys: Vec<String>,
+        days: u32,
+        tier: String,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let task = match keys.as_slice() {
+            [key] => format!("Restore {key}"),
+            _ => format!("Restore {} items", keys.len()),
+        };
+        let bulk = !matches!(keys.as_slice(), [key] if !key.ends_with('/'));
+        let tier = &tier;
+        self.for_each_object(task, &keys, transfer, |api, path| async move {
+            let head = restore::head(&api, &path).await?;
+            match restore::state(api.service(), &head) {
+                RestoreState::Online | RestoreState::Restoring if bulk => Ok(()),
+                RestoreState::Online => anyhow::bail!("{path} is not archived"),
+                RestoreState::Restoring => anyhow::bail!("{path} is already being restored"),
+                _ => restore::restore(&api, &path, &head, days, tier).await,
+            }
+        })
+        .await
+    }
+
+    /// Versions and delete markers of `key`, or of every object below it for
+    /// a folder, and whether there were more than `versions::MAX_VERSIONS`.
+    pub async fn list_versions(&self, key: &str) -> Result<(Vec<ObjectVersion>, bool)> {
+        let (mut list, truncated) = versions::list(&self.api, key).await?;
+        if !key.is_empty() && !key.ends_with('/') {
+            list.retain(|v| v.key == key);
+        }
+        Ok((list, truncated))
+    }
+
+    /// The whole content of a version, for a preview.
+    pub async fn get_version(&self, key: &str, version_id: &str) -> Result<Vec<u8>> {
+        let resp = versions::get(&self.api, key, version_id, 0).await?;
+        Ok(resp.bytes().await?.to_vec())
+    }
+
+    /// Download `version` into `target`, resuming an earlier attempt as the
+    /// content of a version never changes.
+    pub async fn download_version(
+        &self,
+        version: &ObjectVersion,
+        target: PathBuf,
+        transfer: TransferSender,
+    ) -> Result<()> {
+        let key = &version.key;
+        let mut file = PartialFile::resume(
+            &target,
+            PartialMeta {
+                etag: Some(version.version_id.clone()),
+                size: version.size,
+            },
+        )
+        .with_context(|| format!("create `{}`", target.display()))?;
+        let offset = file.written();
+        let send_progress = |transferred_bytes: u64| {
+            transfer
+                .send(TransferType::Download(
+                    key.to_string(),
+                    TransferProgressInfo {
+                        total_bytes: version.size,
+                        transferred_bytes,
+                    },
+                ))
+                .unwrap();
+        };
+        send_progress(offset);
+
+        // A complete partial file was only left unrenamed.
+        if offset == 0 || offset < version.size {
+            let mut resp = versions::get(&self.api, key, &version.version_id, offset).await?;
+            let mut done = offset;
+            while let Some(chunk) = resp.chunk().await? {
+                file.write_all(&chunk).context("write content of file")?;
+                done += chunk.len() as u64;
+                send_progress(done);
+            }
+        }
+        file.finish().context("finish writing to new file")?;
+
+        Ok(())
+    }
+
+    /// Make `version_id` the current version of `key`.
+    pub async fn restore_version(&self, key: &str, version_id: &str) -> Result<()> {
+        versions::restore(&self.api, key, version_id).await
+    }
+
+    /// Delete each `(key, version_id)` for good.
+    pub async fn delete_versions(&self, items: Vec<(String, String)>) -> Vec<BatchItem> {
+        let futures: Vec<_> = items
+            .into_iter()
+            .map(|(key, version_id)| async move {
+                let name = format!("{key} ({version_id})");
+                match versions::delete(&self.api, &key, &version_id).await {
+                    Ok(_) => BatchItem::ok(name),
+                    Err(err) => BatchItem::err(name, err),
+                }
+            })
+            .collect();
+
+        futures::stream::iter(futures)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Run `f` on every object in `keys` concurrently, the objects below
+    /// folders included, reporting the progress as `task`.
+    async fn for_each_object<F, Fut>(
+        &self,
+        task: String,
+        keys: &[String],
+        transfer: TransferSender,
+        f: F,
+    ) -> Result<Vec<BatchItem>>
+    where
+        F: Fn(Api, String) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let paths: Vec<_> = self
+            .expand_keys(keys)
+            .await?
+            .into_iter()
+            .filter(|path| !path.ends_with('/'))
+            .collect();
+        let total = paths.len() as u64;
+        let done = AtomicU64::new(0);
+        let send_progress = |done: u64| {
+            transfer
+                .send(TransferType::Task(
+                    task.clone(),
+                    TransferProgressInfo {
+                        total_bytes: total,
+                        transferred_bytes: done,
+                    },
+                ))
+                .unwrap();
+        };
+        send_progress(0);
+
+        let futures: Vec<_> = paths
+            .into_iter()
+            .map(|path| {
+                let result = f(self.api.clone(), path.clone());
+                let (done, send_progress) = (&done, &send_progress);
+                async move {
+                    let result = result.await;
+                    send_progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+                    match result {
+                        Ok(_) => BatchItem::ok(path),
+                        Err(err) => BatchItem::err(path, err),
+                    }
+                }
+            })
+            .collect();
+        let results = futures::stream::iter(futures)
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await;
+
+        Ok(results)
+    }
+
+    /// Replace the folders in `keys` with all the keys below them, children
+    /// come before their folder.
+    async fn expand_keys(&self, keys: &[String]) -> Result<Vec<String>> {
+        let mut paths = vec![];
+        for key in keys {
+            if key.ends_with('/') {
+                let entries: Vec<_> = self
+                    .operator
+                    .lister_with(key)
+                    .recursive(true)
+                    .await?
+                    .try_collect()
+                    .await?;
+                let mut children: Vec<String> = entries
+                    .iter()
+                    .map(|e| e.path().to_string())
+                    .filter(|path| path != key)
+                    .collect();
+                children.sort_by(|a, b| b.cmp(a));
+                paths.extend(children);
             }
+            paths.push(key.clone());
         }
+        paths.dedup();
 
-        let mut list_objects = ListObjects::default();
-        list_objects.set_start_after(if objects.is_empty() {
-            common_prefixes.last().unwrap().key().to_owned()
+        Ok(paths)
+    }
+
+    /// List one page of `query.prefix`, continuing from `query.cursor`.
+    ///
+    /// A prefix ending with `/` lists a folder, otherwise every entry of the
+    /// folder starting with it.
+    pub async fn list_v2(&self, query: ListObjectsV2Params) -> Result<ListObjects> {
+        tracing::debug!("List object: {:?}", query);
+        let path = query.prefix;
+        let page_size = if query.max_keys == 0 {
+            usize::MAX
         } else {
-            objects.last().unwrap().key().to_owned()
-        });
-        list_objects.set_is_truncated(is_truncated);
+            query.max_keys
+        };
+
+        let mut cursor = query.cursor.inner.lock().await;
+        let (mut common_prefixes, mut objects) = (vec![], vec![]);
+        // Pages may come back short, or empty once folder markers are left
+        // out, keep listing until this one is full.
+        while !cursor.done && common_prefixes.len() + objects.len() < page_size {
+            let page = listing::page(
+                &self.api,
+                &listing::PageQuery {
+                    prefix: &path,
+                    recursive: query.recursive,
+                    max_keys: page_size - common_prefixes.len() - objects.len(),
+                    start_after: &query.start_after,
+                    token: cursor.token.take(),
+                },
+            )
+            .await?;
+            common_prefixes.extend(page.folders);
+            objects.extend(page.objects);
+            cursor.done = page.next.is_none();
+            cursor.token = page.next;
+        }
+
+        let last = [common_prefixes.last(), objects.last()]
+            .into_iter()
+            .flatten()
+            .map(|obj| obj.key())
+            .max()
+            .map(str::to_string);
+        let mut list_objects = ListObjects::new(
+            self.config.bucket.clone(),
+            "/".to_string(),
+            path,
+            last.unwrap_or(query.start_after),
+            query.max_keys.to_string(),
+            !cursor.done,
+        );
+        list_objects.set_generation(query.generation);
         list_objects.set_common_prefixes(common_prefixes);
         list_objects.set_objects(objects);
 
         Ok(list_objects)
     }
 
+    /// Walk `query.prefix` recursively and send the matching objects to
+    /// `on_found` in batches as they are listed.
+    ///
+    /// `on_found` also gets the number of keys scanned so far. Returns the
+    /// number of matches, stopping early once `cancel` is triggered.
+    pub async fn search(
+        &self,
+        query: SearchQuery,
+        cancel: CancelToken,
+        on_found: impl Fn(Vec<Object>, usize),
+    ) -> Result<usize> {
+        tracing::debug!("Search: {:?}", query);
+        let mut lister = self
+            .operator
+            .lister_with(&query.prefix)
+            .recursive(true)
+            .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::LastModified)
+            .await?;
+
+        let (mut scanned, mut found) = (0, 0);
+        let mut batch = vec![];
+        while let Some(entry) = lister.try_next().await? {
+            if cancel.is_cancelled() {
+                tracing::debug!("Search cancelled after {} keys", scanned);
+                return Ok(found);
+            }
+            scanned += 1;
+            let meta = entry.metadata();
+            if meta.is_dir() {
+                continue;
+            }
+            let obj = Object::new(
+                entry.path(),
+                meta.last_modified(),
+                meta.content_length() as usize,
+            );
+            if query.matches(&obj) {
+                batch.push(obj);
+            }
+            if scanned % SEARCH_BATCH_SIZE == 0 {
+                found += batch.len();
+                on_found(std::mem::take(&mut batch), scanned);
+            }
+        }
+        found += batch.len();
+        on_found(batch, scanned);
+
+        Ok(found)
+    }
+
     pub async fn create_folder(&self, path: String) -> Result<bool> {
         let path = if path.ends_with('/') {
             path
@@ -182,42 +668,211 @@ impl Client {
         Ok(result)
     }
 
+    /// Server-side copy `src` to `dest`, a folder is copied with everything
+    /// below it.
+    ///
+    /// For a move the sources are only deleted once every copy succeeded, so
+    /// a failure never loses data.
     pub async fn copy_object(
         &self,
         src: impl AsRef<str>,
         dest: impl AsRef<str>,
         is_move: bool,
-    ) -> Result<(String, bool)> {
-        let src = src.as_ref();
-        let dest = dest.as_ref();
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let pair = (src.as_ref().to_string(), dest.as_ref().to_string());
+        self.copy_pairs(self, vec![pair], is_move, false, transfer)
+            .await
+    }
 
-        tracing::debug!("Copy object: {} to: {}", src, dest);
+    /// Copy or move `keys` of the `source` bucket into the folder `dest` of
+    /// this one, like [`Client::copy_object`].
+    ///
+    /// `source` may be another session, even of another service. Objects are
+    /// copied server-side when both sessions share the service and the
+    /// credentials, otherwise they are streamed through the app.
+    ///
+    /// Unless `overwrite` is set, nothing is copied when a destination key
+    /// already exists and [`OSSError::Conflict`] lists them.
+    pub async fn copy_multi_object(
+        &self,
+        source: &Client,
+        keys: Vec<String>,
+        dest: &str,
+        is_move: bool,
+        overwrite: bool,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let pairs = keys
+            .into_iter()
+            .map(|key| {
+                let mut to = format!("{dest}{}", get_name_form_path(&key));
+                if key.ends_with('/') {
+                    to.push('/');
+                }
+                (key, to)
+            })
+            .collect();
+        self.copy_pairs(source, pairs, is_move, overwrite, transfer)
+            .await
+    }
 
-        self.operator.copy(src, dest).await?;
+    async fn copy_pairs(
+        &self,
+        source: &Client,
+        pairs: Vec<(String, String)>,
+        is_move: bool,
+        overwrite: bool,
+        transfer: TransferSender,
+    ) -> Result<Vec<BatchItem>> {
+        let same_bucket =
+            self.config.same_account(&source.config) && self.config.bucket == source.config.bucket;
+        let mut copies = vec![];
+        for (src, dest) in pairs.iter() {
+            tracing::debug!("Copy object: {} to: {}", src, dest);
+
+            if src.ends_with('/') != dest.ends_with('/') {
+                anyhow::bail!("cannot copy `{src}` to `{dest}`, both must be folders or files");
+            }
+            if same_bucket && src == dest {
+                anyhow::bail!("cannot copy `{src}` onto itself");
+            }
+            if same_bucket && src.ends_with('/') && dest.starts_with(src.as_str()) {
+                anyhow::bail!("cannot copy folder `{src}` into itself");
+            }
 
-        Ok((src.to_string(), is_move))
-    }
+            for from in source.expand_keys(std::slice::from_ref(src)).await? {
+                let to = format!("{dest}{}", &from[src.len()..]);
+                copies.push((from, to));
+            }
+        }
 
-    fn streaming_upload(&self, path: &str) -> Result<BoxedStreamingUploader> {
-        Ok(Box::new(StreamingUploader::new(
-            self.operator.clone(),
-            path.to_string(),
-        )))
-    }
+        if !overwrite {
+            let checks: Vec<_> = copies
+                .iter()
+                .map(|(_, to)| {
+                    let operator = self.operator.clone();
+                    let to = to.clone();
+                    async move { operator.is_exist(&to).await.map(|exist| (to, exist)) }
+                })
+                .collect();
+            let conflicts: Vec<String> = futures::stream::iter(checks)
+                .buffer_unordered(self.config.concurrency.max(1))
+                .try_filter_map(|(to, exist)| async move { Ok(exist.then_some(to)) })
+                .try_collect()
+                .await?;
+            if !conflicts.is_empty() {
+                return Err(OSSError::Conflict(conflicts).into());
+            }
+        }
 
-    async fn streaming_read(&self, path: &str, transfer: TransferSender) -> Result<Vec<u8>> {
-        let reader = self.operator.reader(path).await?;
+        let task = match pairs.as_slice() {
+            [(src, _)] => format!("{} {src}", if is_move { "Move" } else { "Copy" }),
+            _ => format!(
+                "{} {} items",
+                if is_move { "Move" } else { "Copy" },
+                pairs.len()
+            ),
+        };
+        let total = copies.len() as u64;
+        let send_progress = |done: u64| {
+            transfer
+                .send(TransferType::Task(
+                    task.clone(),
+                    TransferProgressInfo {
+                        total_bytes: total,
+                        transferred_bytes: done,
+                    },
+                ))
+                .unwrap();
+        };
+        send_progress(0);
+
+        let sources: Vec<String> = copies.iter().map(|(from, _)| from.clone()).collect();
+        let tasks: Vec<_> = copies
+            .into_iter()
+            .map(|(from, to)| {
+                let client = self.clone();
+                let source = source.clone();
+                let transfer = transfer.clone();
+                async move {
+                    let result = if to.ends_with('/') {
+                        client.operator.create_dir(&to).await.map_err(Into::into)
+                    } else if same_bucket {
+                        client.operator.copy(&from, &to).await.map_err(Into::into)
+                    } else if client.config.same_account(&source.config) {
+                        client.api.copy_from(&source.api, &from, &to).await
+                    } else {
+                        client.stream_from(&source, &from, &to, &transfer).await
+                    };
+                    match result {
+                        Ok(_) => (from, BatchItem::ok(to)),
+                        Err(err) => {
+                            let item = BatchItem::err(to, format!("copy from `{from}`: {err}"));
+                            (from, item)
+                        }
+                    }
+                }
+            })
+            .collect();
+        let mut tasks =
+            futures::stream::iter(tasks).buffer_unordered(self.config.concurrency.max(1));
+
+        let mut results = Vec::with_capacity(total as usize);
+        let mut copied = HashSet::new();
+        while let Some((from, item)) = tasks.next().await {
+            if item.is_ok() {
+                copied.insert(from);
+            }
+            results.push(item);
+            send_progress(results.len() as u64);
+        }
+        drop(tasks);
+
+        if is_move {
+            if results.iter().all(BatchItem::is_ok) {
+                // Only what was copied, objects written under a source
+                // folder since the listing are kept.
+                let sources = sources
+                    .into_iter()
+                    .filter(|from| copied.contains(from))
+                    .collect();
+                let task = task.replacen("Move", "Delete", 1);
+                for item in source.delete_paths(task, sources, &transfer).await? {
+                    if let Some(err) = item.error {
+                        results.push(BatchItem::err(
+                            item.key,
+                            format!("copied but not deleted: {err}"),
+                        ));
+                    }
+                }
+            } else {
+                tracing::warn!("Copy failed, keeping the sources.");
+            }
+        }
 
-        let size = self.meta_data(path).await?.content_length();
-        let mut body = Vec::new();
+        Ok(results)
+    }
 
-        let mut stream = reader
-            .into_futures_async_read(0..)
+    /// Copy `from` of another bucket to `to` by streaming it through the app.
+    async fn stream_from(
+        &self,
+        source: &Client,
+        from: &str,
+        to: &str,
+        transfer: &TransferSender,
+    ) -> Result<()> {
+        let size = source.meta_data(from).await?.content_length();
+        let reader = source
+            .operator
+            .reader(from)
+            .await?
+            .into_futures_async_read(0..size)
             .await?
             .report_progress(|bytes_read| {
                 transfer
-                    .send(TransferType::Download(
-                        path.to_string(),
+                    .send(TransferType::Upload(
+                        to.to_string(),
                         TransferProgressInfo {
                             total_bytes: size,
                             transferred_bytes: bytes_read as u64,
@@ -225,13 +880,37 @@ impl Client {
                     ))
                     .unwrap();
             });
+        let mut writer = self
+            .operator
+            .writer_with(to)
+            .chunk(self.config.part_size)
+            .concurrent(self.config.concurrency)
+            .await?
+            .into_futures_async_write();
 
-        stream
-            .read_to_end(&mut body)
+        futures::io::copy(reader, &mut writer)
             .await
-            .context("failed to read object content into buffer")?;
+            .with_context(|| format!("stream `{from}` to `{to}`"))?;
+        writer.close().await.context("finish upload")?;
 
-        Ok(body)
+        Ok(())
+    }
+
+    async fn streaming_upload(
+        &self,
+        path: &str,
+        content_type: Option<&str>,
+    ) -> Result<BoxedStreamingUploader> {
+        let uploader = StreamingUploader::new(
+            &self.operator,
+            path.to_string(),
+            self.config.part_size,
+            self.config.concurrency,
+            content_type,
+        )
+        .await?;
+
+        Ok(Box::new(uploader))
     }
 
     pub async fn download_file(
@@ -240,85 +919,450 @@ impl Client {
         target: PathBuf,
         transfer: TransferSender,
     ) -> Result<()> {
-        let mut new_file = PartialFile::create(&target)
-            .with_context(|| format!("create `{}`", target.display()))?;
+        self.download_to(obj, &target, |total_bytes, transferred_bytes| {
+            transfer
+                .send(TransferType::Download(
+                    obj.to_string(),
+                    TransferProgressInfo {
+                        total_bytes,
+                        transferred_bytes,
+                    },
+                ))
+                .unwrap();
+        })
+        .await
+    }
+
+    /// Download every object below `prefix` into `target`, recreating the
+    /// key hierarchy as local directories.
+    ///
+    /// Each file shows up in the transfers, and the prefix itself reports the
+    /// progress of all of them together.
+    pub async fn download_dir(
+        &self,
+        prefix: &str,
+        target: PathBuf,
+        transfer: TransferSender,
+    ) -> Result<()> {
+        let entries: Vec<_> = self
+            .operator
+            .lister_with(prefix)
+            .recursive(true)
+            .metakey(Metakey::Mode | Metakey::ContentLength)
+            .await?
+            .try_collect()
+            .await?;
+        std::fs::create_dir_all(&target)
+            .with_context(|| format!("create dir `{}`", target.display()))?;
+
+        let mut files = vec![];
+        for entry in entries.iter() {
+            let Some(relative) = entry.path().strip_prefix(prefix) else {
+                continue;
+            };
+            let mut local = target.clone();
+            for part in relative.split('/') {
+                if !part.is_empty() && part != "." && part != ".." {
+                    local.push(part);
+                }
+            }
+            if entry.metadata().is_dir() {
+                std::fs::create_dir_all(&local)
+                    .with_context(|| format!("create dir `{}`", local.display()))?;
+            } else {
+                files.push((entry.path(), entry.metadata().content_length(), local));
+            }
+        }
+
+        let total: u64 = files.iter().map(|(_, size, _)| size).sum();
+        let send_folder = |transferred_bytes: u64| {
+            transfer
+                .send(TransferType::Download(
+                    prefix.to_string(),
+                    TransferProgressInfo {
+                        total_bytes: total,
+                        transferred_bytes,
+                    },
+                ))
+                .unwrap();
+        };
+        send_folder(0);
+
+        let mut done = 0;
+        let mut errors = vec![];
+        for (key, size, local) in files.iter() {
+            if let Some(parent) = local.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("create dir `{}`", parent.display()))?;
+            }
+            let result = self
+                .download_to(key, local, |total_bytes, transferred_bytes| {
+                    transfer
+                        .send(TransferType::Download(
+                            key.to_string(),
+                            TransferProgressInfo {
+                                total_bytes,
+                                transferred_bytes,
+                            },
+                        ))
+                        .unwrap();
+                    send_folder(done + transferred_bytes);
+                })
+                .await;
+            match result {
+                Ok(_) => done += size,
+                Err(err) => match self.restore_status(key).await {
+                    Ok(state) if !state.is_readable() => {
+                        errors.push(format!("{key}: {}", state.describe()))
+                    }
+                    _ => errors.push(format!("{key}: {err}")),
+                },
+            }
+        }
+        send_folder(done);
+
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "{} of {} files in `{prefix}` failed to download:\n{}",
+                errors.len(),
+                files.len(),
+                errors.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Download `obj` into `target`, `progress` receives the total and the
+    /// downloaded bytes.
+    async fn download_to(
+        &self,
+        obj: &str,
+        target: &Path,
+        progress: impl Fn(u64, u64),
+    ) -> Result<()> {
+        let meta = self.meta_data(obj).await?;
+        let size = meta.content_length();
+        let mut new_file = PartialFile::resume(
+            target,
+            PartialMeta {
+                etag: meta.etag().map(str::to_string),
+                size,
+            },
+        )
+        .with_context(|| format!("create `{}`", target.display()))?;
+        let offset = new_file.written();
+        if offset > 0 {
+            tracing::debug!("Resume download: {} from byte {}", obj, offset);
+        }
 
-        let content = self.streaming_read(obj, transfer).await?;
+        let stream = self
+            .operator
+            .reader(obj)
+            .await?
+            .into_futures_async_read(offset..size)
+            .await?
+            .report_progress(|bytes_read| progress(size, offset + bytes_read as u64));
 
-        new_file
-            .write_all(&content)
+        futures::io::copy(stream, &mut AllowStdIo::new(&mut new_file))
+            .await
             .context("write content of file")?;
         new_file.finish().context("finish writing to new file")?;
 
         Ok(())
     }
 
+    /// Upload a local file or folder into `dest`, folders keep their
+    /// directory structure.
     pub async fn put(&self, path: PathBuf, dest: &str, transfer: &TransferSender) -> Result<()> {
-        let name = get_name(&path);
-        let key = format!("{dest}{name}");
+        if path.is_dir() {
+            return self.put_dir(&path, dest, transfer).await;
+        }
 
-        let mut body = TrackableBodyStream::try_from(path)
-            .map_err(|e| {
-                panic!("Could not open sample file: {e}");
-            })
-            .unwrap();
+        let key = format!("{dest}{}", get_name(&path));
         let progress_tx = transfer.clone();
+        self.put_file(&path, &key, move |key: &str, total: u64, sent: u64| {
+            progress_tx
+                .send(TransferType::Upload(
+                    key.to_string(),
+                    TransferProgressInfo {
+                        total_bytes: total,
+                        transferred_bytes: sent,
+                    },
+                ))
+                .unwrap();
+        })
+        .await
+    }
 
-        body.set_callback(
-            &key,
-            move |key: &str, tot_size: u64, sent: u64, _cur_buf: u64| {
+    /// Upload every file below `dir` to `dest` + `dir name/relative/path`.
+    ///
+    /// Each file shows up in the transfers, and the folder itself reports the
+    /// progress of all of them together.
+    async fn put_dir(&self, dir: &Path, dest: &str, transfer: &TransferSender) -> Result<()> {
+        let folder = format!("{dest}{}/", get_name(dir));
+        let (files, empty_dirs) =
+            walk_dir(dir).with_context(|| format!("read dir `{}`", dir.display()))?;
+        let key_of = |path: &Path| -> Result<String> {
+            let relative = path
+                .strip_prefix(dir)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Ok(format!("{folder}{relative}"))
+        };
+        // Empty folders only exist as markers.
+        for path in empty_dirs.iter() {
+            self.create_folder(format!("{}/", key_of(path)?)).await?;
+        }
+        if files.is_empty() {
+            if empty_dirs.is_empty() {
+                self.create_folder(folder).await?;
+            }
+            return Ok(());
+        }
+
+        let mut sizes = Vec::with_capacity(files.len());
+        for path in files.iter() {
+            sizes.push(std::fs::metadata(path)?.len());
+        }
+        let total: u64 = sizes.iter().sum();
+        let done = Arc::new(AtomicU64::new(0));
+        let send_folder = {
+            let progress_tx = transfer.clone();
+            let folder = folder.clone();
+            move |transferred_bytes: u64| {
                 progress_tx
                     .send(TransferType::Upload(
-                        key.to_string(),
+                        folder.clone(),
                         TransferProgressInfo {
-                            total_bytes: tot_size,
-                            transferred_bytes: sent,
+                            total_bytes: total,
+                            transferred_bytes,
                         },
                     ))
                     .unwrap();
+            }
+        };
+        send_folder(0);
+
+        let mut errors = vec![];
+        for (path, size) in files.iter().zip(sizes) {
+            let key = key_of(path)?;
+
+            let progress_tx = transfer.clone();
+            let send_folder = send_folder.clone();
+            let uploaded = done.clone();
+            let result = self
+                .put_file(path, &key, move |key: &str, total: u64, sent: u64| {
+                    progress_tx
+                        .send(TransferType::Upload(
+                            key.to_string(),
+                            TransferProgressInfo {
+                                total_bytes: total,
+                                transferred_bytes: sent,
+                            },
+                        ))
+                        .unwrap();
+                    send_folder(uploaded.load(Ordering::Relaxed) + sent);
+                })
+                .await;
+            match result {
+                Ok(_) => {
+                    done.fetch_add(size, Ordering::Relaxed);
+                }
+                Err(err) => errors.push(format!("{key}: {err}")),
+            }
+        }
+        send_folder(done.load(Ordering::Relaxed));
+
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "{} of {} files in `{folder}` failed to upload:\n{}",
+                errors.len(),
+                files.len(),
+                errors.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Upload a single file to `key`, `progress` receives the key, the total
+    /// and the uploaded bytes.
+    async fn put_file(
+        &self,
+        path: &Path,
+        key: &str,
+        progress: impl Fn(&str, u64, u64) + Send + Sync + 'static,
+    ) -> Result<()> {
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("open `{}`", path.display()))?
+            .len();
+        let content_type = content_type::detect(path, &self.content_types.read().unwrap());
+        tracing::debug!("Content-Type of {}: {:?}", key, content_type);
+        if size > self.config.part_size as u64 {
+            let upload =
+                ResumableUpload::start(&self.api, path, key, self.config.part_size, content_type)
+                    .await?;
+            return upload
+                .run(self.config.concurrency, |sent| progress(key, size, sent))
+                .await;
+        }
+
+        let mut body = TrackableBodyStream::try_from(path.to_path_buf())
+            .with_context(|| format!("open `{}`", path.display()))?;
+        body.set_callback(
+            key,
+            move |key: &str, tot_size: u64, sent: u64, _cur_buf: u64| {
+                progress(key, tot_size, sent);
             },
         );
 
-        let mut uploader = self.streaming_upload(&key)?;
-        while let Ok(Some(bytes)) = body.try_next().await {
-            uploader.write_bytes(bytes).await?;
+        let mut uploader = self.streaming_upload(key, content_type.as_deref()).await?;
+        loop {
+            let result = match body.try_next().await {
+                Ok(Some(bytes)) => uploader.write_bytes(bytes).await,
+                Ok(None) => break,
+                Err(err) => Err(anyhow::anyhow!("read `{}`: {err}", path.display())),
+            };
+            if let Err(err) = result {
+                // The upload failed already, report why rather than the abort.
+                if let Err(abort_err) = uploader.abort().await {
+                    tracing::warn!("Could not abort upload of `{}`: {}", key, abort_err);
+                }
+                return Err(err);
+            }
         }
         uploader.finish().await?;
-        // TODO: check if put success
 
         Ok(())
     }
 
+    /// Upload each of `paths` into `dest`, failing with the paths that could
+    /// not be uploaded once all were tried.
     pub async fn put_multi(
         &self,
         paths: Vec<PathBuf>,
         dest: String,
         transfer: TransferSender,
-    ) -> Result<Vec<String>> {
-        let mut results = vec![];
+    ) -> Result<()> {
+        let total = paths.len();
+        let mut errors = vec![];
         for path in paths {
-            match self.put(path, &dest, &transfer).await {
-                Ok(_) => results.push("upload success".into()),
-                Err(err) => results.push(err.to_string()),
+            let name = path.display().to_string();
+            if let Err(err) = self.put(path, &dest, &transfer).await {
+                errors.push(format!("{name}: {err:#}"));
             }
         }
 
-        Ok(results)
+        match errors.as_slice() {
+            [] => Ok(()),
+            [err] if total == 1 => anyhow::bail!("{err}"),
+            _ => anyhow::bail!(
+                "{} of {total} uploads failed:\n{}",
+                errors.len(),
+                errors.join("\n")
+            ),
+        }
     }
 
+    /// A URL to read `object` until it expires. `params` are added to its
+    /// query, like the `response-content-disposition` and
+    /// `response-content-type` overrides or an OSS `x-oss-process`.
     pub async fn signature_url(
         &self,
         object: &str,
         expire: u64,
-        // TODO: join the params like: x-oss-image=
-        _params: Option<Params>,
+        params: Option<Params>,
     ) -> Result<String> {
-        let url = self
-            .operator
-            .presign_read(object, std::time::Duration::from_secs(expire))
+        let params = params.unwrap_or_default();
+        let expire = std::time::Duration::from_secs(expire);
+        // The response overrides are supported by every service.
+        if params
+            .keys()
+            .all(|k| RESPONSE_OVERRIDES.contains(&k.as_str()))
+        {
+            let mut op = self.operator.presign_read_with(object, expire);
+            for (key, value) in params.iter() {
+                let value = value.as_deref().unwrap_or_default();
+                op = match key.as_str() {
+                    "response-content-disposition" => op.override_content_disposition(value),
+                    "response-content-type" => op.override_content_type(value),
+                    _ => op.override_cache_control(value),
+                };
+            }
+            return Ok(op.await?.uri().to_string());
+        }
+
+        let mut req = self.api.object(http::Method::GET, object);
+        for (key, value) in params {
+            req = req.query(key, value.unwrap_or_default());
+        }
+        req.presign(expire)
+    }
+
+    /// Signed URLs of every object in `keys`, the objects below folders
+    /// included, sorted by key, with the objects that could not be signed.
+    pub async fn signature_urls(
+        &self,
+        keys: Vec<String>,
+        expire: u64,
+        transfer: TransferSender,
+    ) -> Result<(SignedLinks, Vec<BatchItem>)> {
+        let task = match keys.as_slice() {
+            [key] => format!("Sign links of {key}"),
+            _ => format!("Sign links of {} items", keys.len()),
+        };
+        let expires_at = chrono::Utc::now() + chrono::Duration::seconds(expire as i64);
+        let urls = std::sync::Mutex::new(vec![]);
+        let items = self
+            .for_each_object(task, &keys, transfer, |_, path| {
+                let urls = &urls;
+                async move {
+                    let url = self.signature_url(&path, expire, None).await?;
+                    urls.lock().unwrap().push((path, url));
+                    Ok(())
+                }
+            })
             .await?;
+        let mut links = urls.into_inner().unwrap();
+        links.sort();
+
+        Ok((SignedLinks { links, expires_at }, items))
+    }
+
+    /// A URL anyone can PUT `object` to until it expires. With a
+    /// `content_type`, uploads must send that exact Content-Type.
+    pub async fn presign_upload(
+        &self,
+        object: &str,
+        expire: u64,
+        content_type: Option<&str>,
+    ) -> Result<String> {
+        let mut op = self
+            .operator
+            .presign_write_with(object, std::time::Duration::from_secs(expire));
+        if let Some(content_type) = content_type.filter(|t| !t.is_empty()) {
+            op = op.content_type(content_type);
+        }
+        let req = op.await?;
+
+        Ok(req.uri().to_string())
+    }
+
+    pub fn supports_post_policy(&self) -> bool {
+        post_policy::is_supported(&self.config.service)
+    }
 
-        Ok(url.uri().to_string())
+    /// Sign a policy for browser form uploads, see [`PostPolicy`].
+    pub fn post_policy(&self, params: &PostPolicyParams) -> Result<PostPolicy> {
+        post_policy::sign(
+            &self.api,
+            &self.config.access_key_id,
+            &self.config.access_key_secret,
+            params,
+        )
     }
 }
 
@@ -352,6 +1396,37 @@ impl ClientBuilder {
         self
     }
 
+    /// Size of each part sent during a multipart upload.
+    pub fn part_size(mut self, part_size: usize) -> Self {
+        self.config.part_size = part_size;
+        self
+    }
+
+    /// Content-Type of uploads by file extension, looked up before the
+    /// built-in table.
+    pub fn content_types(mut self, content_types: ContentTypes) -> Self {
+        self.config.content_types = content_types;
+        self
+    }
+
+    /// Base URL of public links, a custom domain or CDN serving the bucket.
+    pub fn cname(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        let base_url = base_url.trim().trim_end_matches('/');
+        self.config.cname = if base_url.is_empty() || base_url.contains("://") {
+            base_url.to_string()
+        } else {
+            format!("https://{base_url}")
+        };
+        self
+    }
+
+    /// Number of parts uploaded at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.config.concurrency = concurrency.max(1);
+        self
+    }
+
     pub fn build(self) -> Result<Client> {
         Client::new(self.config)
     }
@@ -1,7 +1,13 @@
 #![allow(dead_code)]
+use crate::content_type::ContentTypes;
 use cc_core::ServiceType;
 use std::{fmt::Debug, time::Duration};
 
+/// Default size of a single multipart upload part (8 MiB).
+pub(crate) const DEFAULT_PART_SIZE: usize = 8 * 1024 * 1024;
+/// Default number of parts uploaded at the same time.
+pub(crate) const DEFAULT_CONCURRENCY: usize = 4;
+
 #[derive(Debug)]
 pub(crate) struct HttpTimeout {
     pub(crate) connect: Duration,
@@ -35,7 +41,8 @@ pub(crate) struct ClientConfig {
     pub(crate) debug: bool,
     pub(crate) timeout: Duration,
     pub(crate) security_token: String,
-    pub(crate) cname: bool,
+    /// Base URL of public links through a custom domain or CDN, empty for none.
+    pub(crate) cname: String,
     pub(crate) http_timeout: Option<HttpTimeout>,
     pub(crate) http_max_conns: Option<HttpMaxConns>,
     pub(crate) http_proxy: Option<HttpProxy>,
@@ -44,10 +51,25 @@ pub(crate) struct ClientConfig {
     pub(crate) enable_crc: bool,   //TODO: turn on CRC data check
     pub(crate) log_level: i8,
     pub(crate) upload_limit_speed: i64,
+    pub(crate) part_size: usize,
+    pub(crate) concurrency: usize,
+    /// Content-Type of uploads by file extension, before the built-in table.
+    pub(crate) content_types: ContentTypes,
     //...
     pub(crate) additional_headers: Vec<String>,
 }
 
+impl ClientConfig {
+    /// Whether both configs reach the same service with the same credentials,
+    /// so objects can be copied server-side between their buckets.
+    pub(crate) fn same_account(&self, other: &ClientConfig) -> bool {
+        self.service == other.service
+            && self.endpoint == other.endpoint
+            && self.access_key_id == other.access_key_id
+            && self.access_key_secret == other.access_key_secret
+    }
+}
+
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
@@ -69,6 +91,9 @@ impl Default for ClientConfig {
             enable_crc: Default::default(),
             log_level: Default::default(),
             upload_limit_speed: Default::default(),
+            part_size: DEFAULT_PART_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            content_types: Default::default(),
             additional_headers: Default::default(),
         }
     }
@@ -0,0 +1,34 @@
+//! Content-Type of uploaded files, so that browsers display them instead of
+//! downloading them.
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Number of bytes read to sniff files of unknown extension.
+const SNIFF_LEN: usize = 8192;
+
+/// Extensions without the dot, in lowercase, mapped to a MIME type.
+pub type ContentTypes = BTreeMap<String, String>;
+
+/// MIME type of `path` by its extension, the `overrides` first, or by its
+/// content when the extension is unknown.
+pub fn detect(path: &Path, overrides: &ContentTypes) -> Option<String> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    if let Some(ext) = &ext {
+        if let Some(mime) = overrides.get(ext.as_str()) {
+            return Some(mime.clone());
+        }
+        if let Some(mime) = mime_guess2::from_ext(ext).first_raw() {
+            return Some(mime.to_string());
+        }
+    }
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut head))
+        .ok()?;
+    infer::get(&head).map(|t| t.mime_type().to_string())
+}
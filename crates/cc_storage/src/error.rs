@@ -7,6 +7,8 @@ pub enum OSSError {
     ServiceError(u16, String, String, String),
     #[error("{0}")]
     WithDescription(String),
+    #[error("{} destination keys already exist", .0.len())]
+    Conflict(Vec<String>),
 }
 
 pub type ObjectResult<T> = std::result::Result<T, anyhow::Error>;
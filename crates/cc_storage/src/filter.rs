@@ -0,0 +1,172 @@
+use crate::Result;
+use anyhow::Context;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Names starting with the pattern, listed by the server.
+    #[default]
+    Prefix,
+    /// Names containing the pattern, ignoring case.
+    Contains,
+    /// Shell style pattern with `*`, `?` and `[...]`.
+    Glob,
+    Regex,
+}
+
+impl FilterMode {
+    pub const ALL: [FilterMode; 4] = [
+        FilterMode::Prefix,
+        FilterMode::Contains,
+        FilterMode::Glob,
+        FilterMode::Regex,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterMode::Prefix => "Prefix",
+            FilterMode::Contains => "Contains",
+            FilterMode::Glob => "Glob",
+            FilterMode::Regex => "Regex",
+        }
+    }
+}
+
+/// Matches object names against a pattern typed by the user.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    mode: FilterMode,
+    pattern: String,
+    regex: Option<Regex>,
+}
+
+impl Filter {
+    pub fn new(mode: FilterMode, pattern: impl Into<String>) -> Result<Self> {
+        let pattern = pattern.into();
+        let regex = match mode {
+            _ if pattern.is_empty() => None,
+            FilterMode::Prefix => None,
+            FilterMode::Contains => Some(
+                RegexBuilder::new(&regex::escape(&pattern))
+                    .case_insensitive(true)
+                    .build()?,
+            ),
+            FilterMode::Glob => Some(Regex::new(&glob_to_regex(&pattern))?),
+            FilterMode::Regex => {
+                Some(Regex::new(&pattern).with_context(|| format!("invalid regex `{pattern}`"))?)
+            }
+        };
+
+        Ok(Filter {
+            mode,
+            pattern,
+            regex,
+        })
+    }
+
+    pub fn mode(&self) -> FilterMode {
+        self.mode
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+
+    /// Whether the filter is applied by listing a prefix on the server.
+    pub fn is_server_side(&self) -> bool {
+        self.mode == FilterMode::Prefix
+    }
+
+    /// Byte ranges of `name` to highlight, `None` if it does not match.
+    pub fn find(&self, name: &str) -> Option<Vec<Range<usize>>> {
+        if self.is_empty() {
+            return Some(vec![]);
+        }
+        match &self.regex {
+            None => name
+                .starts_with(&self.pattern)
+                .then(|| std::iter::once(0..self.pattern.len()).collect()),
+            Some(regex) => {
+                let ranges: Vec<_> = regex
+                    .find_iter(name)
+                    .map(|m| m.range())
+                    .filter(|r| !r.is_empty())
+                    .collect();
+                (!ranges.is_empty() || regex.is_match(name)).then_some(ranges)
+            }
+        }
+    }
+}
+
+/// Translate a glob into an anchored regex.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let mut class = String::new();
+                let mut closed = false;
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    class.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        class.push('\\');
+                    }
+                    class.push(c);
+                }
+                if closed {
+                    regex.push('[');
+                    regex.push_str(&class);
+                    regex.push(']');
+                } else {
+                    regex.push_str(&regex::escape(&format!("[{class}")));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(pairs: &[(usize, usize)]) -> Option<Vec<Range<usize>>> {
+        Some(pairs.iter().map(|(start, end)| *start..*end).collect())
+    }
+
+    #[test]
+    fn test_filter_modes() {
+        let glob = Filter::new(FilterMode::Glob, "*.jp?g").unwrap();
+        assert_eq!(glob.find("cat.jpeg"), ranges(&[(0, 8)]));
+        assert_eq!(glob.find("cat.png"), None);
+        let glob = Filter::new(FilterMode::Glob, "img_[!0-4]*").unwrap();
+        assert!(glob.find("img_7.png").is_some());
+        assert!(glob.find("img_3.png").is_none());
+
+        let contains = Filter::new(FilterMode::Contains, "Log").unwrap();
+        assert_eq!(contains.find("app.log.1"), ranges(&[(4, 7)]));
+
+        let prefix = Filter::new(FilterMode::Prefix, "2023").unwrap();
+        assert_eq!(prefix.find("2023-01.csv"), ranges(&[(0, 4)]));
+        assert_eq!(prefix.find("x2023"), None);
+
+        assert!(Filter::new(FilterMode::Regex, "(").is_err());
+    }
+}
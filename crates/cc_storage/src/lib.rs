@@ -1,20 +1,37 @@
 pub type Result<T> = anyhow::Result<T>;
 
+mod api;
+mod bucket;
 mod client;
 mod config;
+pub mod content_type;
 mod error;
+pub mod filter;
+pub mod link_export;
+mod listing;
+pub mod meta;
+mod multipart;
 mod partial_file;
+pub mod post_policy;
+pub mod restore;
+pub mod search;
 mod services;
+pub mod storage_class;
 mod stream;
+pub mod tagging;
 mod transfer;
 mod types;
 pub mod util;
 mod version;
+pub mod versions;
 
 pub use cc_core::ServiceType;
 pub use client::Client;
 pub use error::OSSError;
 pub use opendal::{Lister, Metadata};
 pub use transfer::TransferManager;
-pub use types::{Bucket, Headers, ListObjects, ListObjectsV2Params, Object, ObjectType, Params};
+pub use types::{
+    BatchItem, Bucket, BucketACL, Headers, ListObjects, ListObjectsV2Params, Object, ObjectType,
+    Params, Versioning,
+};
 pub use version::VERSION;
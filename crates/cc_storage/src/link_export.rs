@@ -0,0 +1,110 @@
+//! Signed links of many objects, exported as CSV, JSON or plain text.
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+    /// One URL per line.
+    Text,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Text];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Text => "Text",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Text => "txt",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SignedLinks {
+    /// `(key, url)` sorted by key.
+    pub links: Vec<(String, String)>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl SignedLinks {
+    pub fn expires_string(&self) -> String {
+        self.expires_at.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    pub fn render(&self, format: ExportFormat) -> String {
+        let expires = self.expires_string();
+        match format {
+            ExportFormat::Csv => {
+                let mut text = String::from("key,url,expires\n");
+                for (key, url) in &self.links {
+                    text.push_str(&format!(
+                        "{},{},{expires}\n",
+                        csv_field(key),
+                        csv_field(url)
+                    ));
+                }
+                text
+            }
+            ExportFormat::Json => {
+                let links: Vec<_> = self
+                    .links
+                    .iter()
+                    .map(|(key, url)| json!({ "key": key, "url": url, "expires": expires }))
+                    .collect();
+                serde_json::to_string_pretty(&links).unwrap_or_default()
+            }
+            ExportFormat::Text => self
+                .links
+                .iter()
+                .map(|(_, url)| format!("{url}\n"))
+                .collect(),
+        }
+    }
+}
+
+/// Quote fields holding a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_csv_quotes_fields() {
+        let links = SignedLinks {
+            links: vec![
+                ("a.png".into(), "https://b/a.png?x=1".into()),
+                ("c, \"d\".txt".into(), "https://b/c?y=2".into()),
+            ],
+            expires_at: DateTime::from_timestamp(0, 0).unwrap(),
+        };
+        assert_eq!(
+            links.render(ExportFormat::Csv),
+            "key,url,expires\n\
+             a.png,https://b/a.png?x=1,1970-01-01T00:00:00Z\n\
+             \"c, \"\"d\"\".txt\",https://b/c?y=2,1970-01-01T00:00:00Z\n"
+        );
+        assert_eq!(
+            links.render(ExportFormat::Text),
+            "https://b/a.png?x=1\nhttps://b/c?y=2\n"
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Pages of the REST listing of each service. Unlike the listing of opendal,
+//! it returns the storage class (access tier on Azure) of every object.
+use crate::api::Api;
+use crate::types::Object;
+use crate::Result;
+use cc_core::ServiceType;
+use chrono::{DateTime, Utc};
+use http::Method;
+use serde::Deserialize;
+
+/// Largest page S3, OSS and GCS return.
+const MAX_PAGE: usize = 1000;
+
+pub(crate) struct PageQuery<'a> {
+    pub prefix: &'a str,
+    pub recursive: bool,
+    pub max_keys: usize,
+    /// Where a new listing starts, Azure can not start after a key.
+    pub start_after: &'a str,
+    /// Continuation of the previous page.
+    pub token: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct Page {
+    pub folders: Vec<Object>,
+    pub objects: Vec<Object>,
+    /// Continuation of the listing, `None` after the last page.
+    pub next: Option<String>,
+}
+
+pub(crate) async fn page(api: &Api, query: &PageQuery<'_>) -> Result<Page> {
+    let mut page = match api.service() {
+        ServiceType::Gcs => list_gcs(api, query).await?,
+        ServiceType::Azblob => list_azblob(api, query).await?,
+        _ => list_s3(api, query).await?,
+    };
+    // Some services return the folder itself, and folders are only markers
+    // in a recursive listing.
+    page.objects
+        .retain(|obj| obj.key() != query.prefix && !obj.key().ends_with('/'));
+    page.next = page.next.filter(|t| !t.is_empty());
+
+    Ok(page)
+}
+
+fn object(key: &str, last_modified: Option<DateTime<Utc>>, size: u64, class: String) -> Object {
+    let mut obj = Object::new(key, last_modified, size as usize);
+    obj.set_storage_class(class);
+    obj
+}
+
+fn rfc3339(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct ListBucketResult {
+    next_continuation_token: Option<String>,
+    #[serde(rename = "$value")]
+    entries: Vec<S3Entry>,
+}
+
+/// Objects and prefixes of a ListObjectsV2 page.
+#[derive(Deserialize)]
+enum S3Entry {
+    Contents(S3Object),
+    CommonPrefixes(S3Prefix),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct S3Object {
+    key: String,
+    last_modified: String,
+    size: u64,
+    storage_class: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct S3Prefix {
+    prefix: String,
+}
+
+fn parse_s3(text: &str) -> Result<Page> {
+    let result: ListBucketResult = quick_xml::de::from_str(text)?;
+    let mut page = Page {
+        next: result.next_continuation_token,
+        ..Default::default()
+    };
+    for entry in result.entries {
+        match entry {
+            S3Entry::Contents(c) => page.objects.push(object(
+                &c.key,
+                rfc3339(&c.last_modified),
+                c.size,
+                c.storage_class,
+            )),
+            S3Entry::CommonPrefixes(p) => page.folders.push(Object::new_folder(&p.prefix)),
+            S3Entry::Other => {}
+        }
+    }
+
+    Ok(page)
+}
+
+/// ListObjectsV2, also supported by OSS.
+async fn list_s3(api: &Api, query: &PageQuery<'_>) -> Result<Page> {
+    let mut req = api
+        .bucket(Method::GET)
+        .query("list-type", "2")
+        .query("prefix", query.prefix)
+        .query("max-keys", query.max_keys.clamp(1, MAX_PAGE).to_string());
+    if !query.recursive {
+        req = req.query("delimiter", "/");
+    }
+    req = match &query.token {
+        Some(token) => req.query("continuation-token", token),
+        None if !query.start_after.is_empty() => req.query("start-after", query.start_after),
+        None => req,
+    };
+    let resp = req.send().await?;
+    parse_s3(&String::from_utf8_lossy(&resp.body))
+}
+
+async fn list_gcs(api: &Api, query: &PageQuery<'_>) -> Result<Page> {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "camelCase", default)]
+    struct Objects {
+        items: Vec<Item>,
+        prefixes: Vec<String>,
+        next_page_token: Option<String>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "camelCase", default)]
+    struct Item {
+        name: String,
+        size: String,
+        updated: String,
+        storage_class: String,
+    }
+
+    let path = format!("storage/v1/b/{}/o", api.bucket_name());
+    let mut req = api
+        .endpoint(Method::GET, &path)
+        .query("prefix", query.prefix)
+        .query("maxResults", query.max_keys.clamp(1, MAX_PAGE).to_string())
+        .query(
+            "fields",
+            "items(name,size,updated,storageClass),prefixes,nextPageToken",
+        );
+    if !query.recursive {
+        req = req.query("delimiter", "/");
+    }
+    req = match &query.token {
+        Some(token) => req.query("pageToken", token),
+        // The offset is inclusive, the key itself is dropped below.
+        None if !query.start_after.is_empty() => req.query("startOffset", query.start_after),
+        None => req,
+    };
+    let result: Objects = req.send().await?.json()?;
+
+    Ok(Page {
+        folders: result
+            .prefixes
+            .iter()
+            .map(|p| Object::new_folder(p))
+            .collect(),
+        objects: result
+            .items
+            .into_iter()
+            .filter(|i| query.token.is_some() || i.name != query.start_after)
+            .map(|i| {
+                object(
+                    &i.name,
+                    rfc3339(&i.updated),
+                    i.size.parse().unwrap_or_default(),
+                    i.storage_class,
+                )
+            })
+            .collect(),
+        next: result.next_page_token,
+    })
+}
+
+async fn list_azblob(api: &Api, query: &PageQuery<'_>) -> Result<Page> {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct EnumerationResults {
+        blobs: Blobs,
+        next_marker: Option<String>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Blobs {
+        #[serde(rename = "$value")]
+        entries: Vec<Entry>,
+    }
+    /// Blobs and prefixes are interleaved in the listing.
+    #[derive(Deserialize)]
+    enum Entry {
+        Blob(Blob),
+        BlobPrefix(BlobPrefix),
+        #[serde(other)]
+        Other,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Blob {
+        name: String,
+        properties: Properties,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Properties {
+        #[serde(rename = "Last-Modified")]
+        last_modified: String,
+        #[serde(rename = "Content-Length")]
+        content_length: u64,
+        #[serde(rename = "AccessTier")]
+        access_tier: String,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct BlobPrefix {
+        name: String,
+    }
+
+    let mut req = api
+        .bucket(Method::GET)
+        .query("restype", "container")
+        .query("comp", "list")
+        .query("prefix", query.prefix)
+        .query("maxresults", query.max_keys.clamp(1, 5000).to_string());
+    if !query.recursive {
+        req = req.query("delimiter", "/");
+    }
+    if let Some(token) = &query.token {
+        req = req.query("marker", token);
+    }
+    let result: EnumerationResults = req.send().await?.xml()?;
+
+    let mut page = Page {
+        next: result.next_marker,
+        ..Default::default()
+    };
+    for entry in result.blobs.entries {
+        match entry {
+            Entry::Blob(b) => page.objects.push(object(
+                &b.name,
+                DateTime::parse_from_rfc2822(&b.properties.last_modified)
+                    .ok()
+                    .map(|d| d.with_timezone(&Utc)),
+                b.properties.content_length,
+                b.properties.access_tier,
+            )),
+            Entry::BlobPrefix(p) => page.folders.push(Object::new_folder(&p.name)),
+            Entry::Other => {}
+        }
+    }
+
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_s3_page() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult>
+  <Name>bucket</Name>
+  <Prefix>photos/</Prefix>
+  <KeyCount>3</KeyCount>
+  <IsTruncated>true</IsTruncated>
+  <Contents>
+    <Key>photos/a.jpg</Key>
+    <LastModified>2024-01-02T03:04:05.000Z</LastModified>
+    <Size>42</Size>
+    <StorageClass>GLACIER</StorageClass>
+  </Contents>
+  <CommonPrefixes><Prefix>photos/2023/</Prefix></CommonPrefixes>
+  <Contents>
+    <Key>photos/b.jpg</Key>
+    <Size>7</Size>
+    <StorageClass>STANDARD</StorageClass>
+  </Contents>
+  <NextContinuationToken>token</NextContinuationToken>
+</ListBucketResult>"#;
+        let page = parse_s3(text).unwrap();
+        assert_eq!(page.next.as_deref(), Some("token"));
+        assert_eq!(page.folders.len(), 1);
+        assert_eq!(page.folders[0].key(), "photos/2023/");
+        let classes: Vec<_> = page
+            .objects
+            .iter()
+            .map(|o| (o.key(), o.size(), o.storage_class()))
+            .collect();
+        assert_eq!(
+            classes,
+            [
+                ("photos/a.jpg", 42, "GLACIER"),
+                ("photos/b.jpg", 7, "STANDARD")
+            ]
+        );
+        assert!(page.objects[0].last_modified().is_some());
+    }
+}
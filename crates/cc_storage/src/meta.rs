@@ -0,0 +1,306 @@
+//! System and user metadata of objects. Apart from Azure, metadata can only be
+//! changed by copying the object onto itself with replaced metadata.
+use crate::api::Api;
+use crate::error::OSSError;
+use crate::restore::{self, RestoreState};
+use crate::tagging::Tags;
+use crate::Result;
+use cc_core::ServiceType;
+use http::Method;
+use serde::Deserialize;
+
+/// Editable system headers, in display order.
+pub const SYSTEM_HEADERS: [&str; 5] = [
+    "Content-Type",
+    "Cache-Control",
+    "Content-Disposition",
+    "Content-Encoding",
+    "Content-Language",
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct ObjectMeta {
+    /// Every header of the object, for display.
+    pub headers: Vec<(String, String)>,
+    /// Values of `SYSTEM_HEADERS`, empty when unset.
+    pub system: Vec<(&'static str, String)>,
+    /// User metadata without the service prefix, keys are lowercase.
+    pub user: Vec<(String, String)>,
+    /// Tags of the object, `None` where tags are not supported.
+    pub tags: Option<Tags>,
+    /// Whether the content can be read, for archived objects.
+    pub restore: RestoreState,
+    /// Kept by the copy, the services reset it to standard otherwise.
+    pub(crate) storage_class: Option<String>,
+    /// Content length, a single copy request is limited in size.
+    pub(crate) size: u64,
+}
+
+/// Changes applied to the metadata of each object of a selection.
+#[derive(Clone, Debug, Default)]
+pub struct MetaPatch {
+    /// System headers to set, an empty value removes the header.
+    pub system: Vec<(&'static str, String)>,
+    pub user_set: Vec<(String, String)>,
+    pub user_remove: Vec<String>,
+    /// Replace all the user metadata with `user_set`.
+    pub replace_user: bool,
+}
+
+impl ObjectMeta {
+    pub fn apply(&mut self, patch: &MetaPatch) {
+        for (name, value) in &patch.system {
+            match self.system.iter_mut().find(|(n, _)| n == name) {
+                Some((_, v)) => *v = value.clone(),
+                None => self.system.push((name, value.clone())),
+            }
+        }
+        if patch.replace_user {
+            self.user.clear();
+        }
+        self.user
+            .retain(|(k, _)| !patch.user_remove.iter().any(|r| r.eq_ignore_ascii_case(k)));
+        for (key, value) in &patch.user_set {
+            let key = key.trim().to_lowercase();
+            if key.is_empty() {
+                continue;
+            }
+            match self.user.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, v)) => *v = value.clone(),
+                None => self.user.push((key, value.clone())),
+            }
+        }
+    }
+}
+
+fn user_prefix(service: &ServiceType) -> &'static str {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible => "x-amz-meta-",
+        ServiceType::Oss => "x-oss-meta-",
+        ServiceType::Gcs => "x-goog-meta-",
+        ServiceType::Azblob => "x-ms-meta-",
+    }
+}
+
+pub(crate) fn storage_class_header(service: &ServiceType) -> &'static str {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible => "x-amz-storage-class",
+        ServiceType::Oss => "x-oss-storage-class",
+        ServiceType::Gcs => "x-goog-storage-class",
+        ServiceType::Azblob => "x-ms-access-tier",
+    }
+}
+
+pub(crate) async fn get(api: &Api, key: &str) -> Result<ObjectMeta> {
+    let resp = api.object(Method::HEAD, key).send().await?;
+    let prefix = user_prefix(api.service());
+
+    let mut headers: Vec<_> = resp
+        .headers
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                String::from_utf8_lossy(v.as_bytes()).to_string(),
+            )
+        })
+        .collect();
+    headers.sort();
+    let system = SYSTEM_HEADERS
+        .iter()
+        .map(|name| (*name, resp.header(name).unwrap_or_default().to_string()))
+        .collect();
+    let user = headers
+        .iter()
+        .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?.to_string(), v.clone())))
+        .collect();
+
+    Ok(ObjectMeta {
+        system,
+        user,
+        tags: None,
+        restore: restore::state(api.service(), &resp),
+        storage_class: resp
+            .header(storage_class_header(api.service()))
+            .map(str::to_string),
+        size: resp
+            .header("content-length")
+            .and_then(|len| len.parse().ok())
+            .unwrap_or_default(),
+        headers,
+    })
+}
+
+pub(crate) async fn set(api: &Api, key: &str, meta: &ObjectMeta) -> Result<()> {
+    let service = api.service();
+    let prefix = user_prefix(service);
+
+    if *service == ServiceType::Azblob {
+        // Properties and metadata have their own requests, without a copy.
+        let mut req = api.object(Method::PUT, key).query("comp", "properties");
+        for (name, value) in meta.system.iter().filter(|(_, v)| !v.is_empty()) {
+            req = req.header(&format!("x-ms-blob-{}", name.to_lowercase()), value)?;
+        }
+        req.send().await?;
+        let mut req = api.object(Method::PUT, key).query("comp", "metadata");
+        for (k, v) in &meta.user {
+            req = req.header(&format!("{prefix}{k}"), v)?;
+        }
+        req.send().await?;
+        return Ok(());
+    }
+
+    if let Some(max) = max_copy_size(service).filter(|max| meta.size > *max) {
+        return Err(OSSError::WithDescription(format!(
+            "{key} is larger than {} GB, the largest object {service} can copy onto itself",
+            max >> 30
+        ))
+        .into());
+    }
+
+    let directive = match service {
+        ServiceType::Oss => "x-oss-metadata-directive",
+        ServiceType::Gcs => "x-goog-metadata-directive",
+        _ => "x-amz-metadata-directive",
+    };
+    let mut req = api
+        .object(Method::PUT, key)
+        .copy_source(api, key)?
+        .header(directive, "REPLACE")?;
+    for (name, value) in meta.system.iter().filter(|(_, v)| !v.is_empty()) {
+        req = req.header(name, value)?;
+    }
+    for (k, v) in &meta.user {
+        req = req.header(&format!("{prefix}{k}"), v)?;
+    }
+    if let Some(class) = &meta.storage_class {
+        req = req.header(storage_class_header(service), class)?;
+    }
+    for name in kept_headers(service) {
+        if let Some((_, value)) = meta
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        {
+            req = req.header(name, value)?;
+        }
+    }
+
+    // The copy resets the ACL of the object to the default of the bucket.
+    // Keeping it is best-effort, the metadata is saved anyway.
+    match service {
+        ServiceType::Oss => {
+            match oss_grant(api, key).await {
+                Ok(Some(grant)) => req = req.header("x-oss-object-acl", &grant)?,
+                Ok(None) => {}
+                Err(err) => tracing::warn!("Read the ACL of {key}: {err}"),
+            }
+            req.send_copy().await
+        }
+        ServiceType::S3 | ServiceType::S3Compatible => {
+            let acl = match api.object(Method::GET, key).query("acl", "").send().await {
+                Ok(resp) => Some(String::from_utf8_lossy(&resp.body).to_string()),
+                Err(err) => {
+                    tracing::warn!("Read the ACL of {key}: {err}");
+                    None
+                }
+            };
+            req.send_copy().await?;
+            // Only the grant of the owner is recreated by the copy.
+            let Some(acl) = acl.filter(|acl| acl.matches("<Grant>").count() > 1) else {
+                return Ok(());
+            };
+            if s3_owner_enforced(api).await {
+                return Ok(());
+            }
+            let result = api
+                .object(Method::PUT, key)
+                .query("acl", "")
+                .xml_body(acl)?
+                .send()
+                .await;
+            if let Err(err) = result {
+                tracing::warn!("Restore the ACL of {key}: {err}");
+            }
+            Ok(())
+        }
+        // Access is managed on the bucket, uniformly, in most buckets.
+        _ => req.send_copy().await,
+    }
+}
+
+/// Largest object a single copy request accepts, larger objects would need a
+/// multipart copy.
+fn max_copy_size(service: &ServiceType) -> Option<u64> {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible => Some(5 << 30),
+        ServiceType::Oss => Some(1 << 30),
+        ServiceType::Gcs | ServiceType::Azblob => None,
+    }
+}
+
+/// Headers outside `SYSTEM_HEADERS` a replacing copy drops, sent again when
+/// the object has them.
+fn kept_headers(service: &ServiceType) -> &'static [&'static str] {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible => &[
+            "Expires",
+            "x-amz-website-redirect-location",
+            "x-amz-server-side-encryption",
+            "x-amz-server-side-encryption-aws-kms-key-id",
+            "x-amz-server-side-encryption-bucket-key-enabled",
+        ],
+        ServiceType::Oss => &[
+            "Expires",
+            "x-oss-server-side-encryption",
+            "x-oss-server-side-encryption-key-id",
+        ],
+        ServiceType::Gcs | ServiceType::Azblob => &[],
+    }
+}
+
+/// The canned ACL of an OSS object, `None` when it follows the bucket.
+async fn oss_grant(api: &Api, key: &str) -> Result<Option<String>> {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct AccessControlPolicy {
+        access_control_list: AccessControlList,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct AccessControlList {
+        grant: String,
+    }
+
+    let policy: AccessControlPolicy = api
+        .object(Method::GET, key)
+        .query("acl", "")
+        .send()
+        .await?
+        .xml()?;
+    let grant = policy.access_control_list.grant;
+    Ok((!grant.is_empty() && grant != "default").then_some(grant))
+}
+
+/// ACLs are disabled on S3 buckets with the `BucketOwnerEnforced` ownership,
+/// setting one fails. Buckets without ownership controls still use them.
+async fn s3_owner_enforced(api: &Api) -> bool {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct OwnershipControls {
+        rule: Rule,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Rule {
+        object_ownership: String,
+    }
+
+    let result = api
+        .bucket(Method::GET)
+        .query("ownershipControls", "")
+        .send()
+        .await
+        .and_then(|resp| resp.xml::<OwnershipControls>());
+    matches!(result, Ok(c) if c.rule.object_ownership == "BucketOwnerEnforced")
+}
@@ -0,0 +1,415 @@
+//! Multipart uploads that survive a failure or a restart of the app.
+//!
+//! Progress is persisted as a checkpoint under the data directory, so a new
+//! attempt on the same file only sends the parts that are missing.
+use crate::api::Api;
+use crate::error::OSSError;
+use crate::Result;
+use anyhow::Context;
+use base64::Engine;
+use cc_core::{store, ServiceType};
+use futures::StreamExt;
+use http::Method;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Smallest part accepted by S3 compatible services, except for the last one.
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_PARTS: u64 = 10_000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CompletedPart {
+    number: u64,
+    etag: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct UploadCheckpoint {
+    key: String,
+    upload_id: String,
+    file_size: u64,
+    modified: u64,
+    part_size: u64,
+    parts: Vec<CompletedPart>,
+    /// Set on completion by Azure, on initiation by the other services.
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+impl UploadCheckpoint {
+    /// Whether the checkpoint was written for the file as it is now.
+    fn matches(&self, file_size: u64, modified: u64) -> bool {
+        self.file_size == file_size && self.modified == modified
+    }
+
+    fn total_parts(&self) -> u64 {
+        self.file_size.div_ceil(self.part_size).max(1)
+    }
+
+    /// Length of the part `number`, counted from 1, only the last one is
+    /// shorter.
+    fn part_len(&self, number: u64) -> u64 {
+        self.part_size
+            .min(self.file_size - (number - 1) * self.part_size)
+    }
+
+    /// Numbers of the parts not uploaded yet.
+    fn pending(&self) -> Vec<u64> {
+        (1..=self.total_parts())
+            .filter(|n| !self.parts.iter().any(|p| p.number == *n))
+            .collect()
+    }
+
+    /// Bytes of the parts already uploaded.
+    fn sent(&self) -> u64 {
+        self.parts.iter().map(|p| self.part_len(p.number)).sum()
+    }
+}
+
+/// Size and modification time of the file, a checkpoint is only reused when
+/// both are unchanged.
+fn file_stamp(path: &Path) -> Result<(u64, u64)> {
+    let meta = std::fs::metadata(path)?;
+    let modified = meta
+        .modified()?
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs();
+
+    Ok((meta.len(), modified))
+}
+
+/// Parts of at least `requested` bytes, large enough to stay below the part
+/// count limit.
+fn part_size(requested: usize, file_size: u64) -> u64 {
+    (requested as u64)
+        .max(MIN_PART_SIZE)
+        .max(file_size.div_ceil(MAX_PARTS))
+}
+
+pub(crate) struct ResumableUpload {
+    api: Api,
+    id: String,
+    path: PathBuf,
+    checkpoint: UploadCheckpoint,
+}
+
+impl ResumableUpload {
+    /// Pick up the checkpoint of an earlier attempt to upload `path` to `key`,
+    /// or initiate a new multipart upload.
+    pub(crate) async fn start(
+        api: &Api,
+        path: &Path,
+        key: &str,
+        part_size: usize,
+        content_type: Option<String>,
+    ) -> Result<Self> {
+        let (file_size, modified) = file_stamp(path)?;
+        let id = {
+            let raw = format!("{}\n{}", api.object_url(key), path.display());
+            format!("{:x}", Sha256::digest(raw.as_bytes()))
+        };
+
+        if let Some(checkpoint) = store::get_checkpoint::<UploadCheckpoint>(&id) {
+            let upload = Self {
+                api: api.clone(),
+                id: id.clone(),
+                path: path.to_path_buf(),
+                checkpoint,
+            };
+            if upload.checkpoint.matches(file_size, modified) {
+                tracing::debug!(
+                    "Resume upload: {} with {} parts done",
+                    key,
+                    upload.checkpoint.parts.len()
+                );
+                return Ok(upload);
+            }
+            tracing::debug!("Local file changed, drop upload: {}", key);
+            if let Err(err) = upload.abort().await {
+                tracing::warn!("Could not abort stale upload of `{}`: {}", key, err);
+            }
+        }
+
+        let part_size = self::part_size(part_size, file_size);
+        let upload_id = initiate(api, key, content_type.as_deref()).await?;
+        let upload = Self {
+            api: api.clone(),
+            id,
+            path: path.to_path_buf(),
+            checkpoint: UploadCheckpoint {
+                key: key.to_string(),
+                upload_id,
+                file_size,
+                modified,
+                part_size,
+                parts: vec![],
+                content_type,
+            },
+        };
+        upload.save();
+
+        Ok(upload)
+    }
+
+    /// Send the missing parts and complete the upload, `progress` receives
+    /// the number of bytes uploaded so far.
+    pub(crate) async fn run(mut self, concurrency: usize, progress: impl Fn(u64)) -> Result<()> {
+        let mut sent = self.checkpoint.sent();
+        progress(sent);
+
+        let part_size = self.checkpoint.part_size;
+        let pending: Vec<_> = self
+            .checkpoint
+            .pending()
+            .into_iter()
+            .map(|number| (number, self.checkpoint.part_len(number)))
+            .collect();
+        let mut uploads = futures::stream::iter(pending.into_iter().map(|(number, len)| {
+            let api = self.api.clone();
+            let path = self.path.clone();
+            let key = self.checkpoint.key.clone();
+            let upload_id = self.checkpoint.upload_id.clone();
+            async move {
+                let offset = (number - 1) * part_size;
+                let data = read_part(&path, offset, len).await?;
+                let etag = upload_part(&api, &key, &upload_id, number, data).await?;
+                Ok::<_, anyhow::Error>((CompletedPart { number, etag }, len))
+            }
+        }))
+        .buffer_unordered(concurrency.max(1));
+
+        while let Some(result) = uploads.next().await {
+            match result {
+                Ok((part, len)) => {
+                    self.checkpoint.parts.push(part);
+                    self.save();
+                    sent += len;
+                    progress(sent);
+                }
+                Err(err) => {
+                    if is_no_such_upload(&err) {
+                        store::delete_checkpoint(&self.id);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        drop(uploads);
+
+        self.checkpoint.parts.sort_by_key(|p| p.number);
+        let result = complete(&self.api, &self.checkpoint).await;
+        if result.is_ok() || result.as_ref().is_err_and(is_no_such_upload) {
+            store::delete_checkpoint(&self.id);
+        }
+
+        result
+    }
+
+    async fn abort(self) -> Result<()> {
+        store::delete_checkpoint(&self.id);
+        if *self.api.service() == ServiceType::Azblob {
+            // Uncommitted blocks are garbage collected by the service.
+            return Ok(());
+        }
+        self.api
+            .object(Method::DELETE, &self.checkpoint.key)
+            .query("uploadId", &self.checkpoint.upload_id)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    fn save(&self) {
+        if let Err(err) = store::put_checkpoint(&self.id, &self.checkpoint) {
+            tracing::warn!(
+                "Could not save checkpoint of `{}`: {}",
+                self.checkpoint.key,
+                err
+            );
+        }
+    }
+}
+
+async fn read_part(path: &Path, offset: u64, len: u64) -> Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("open `{}`", path.display()))?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut data = vec![0; len as usize];
+    file.read_exact(&mut data)
+        .await
+        .with_context(|| format!("read `{}` at {offset}", path.display()))?;
+
+    Ok(data)
+}
+
+async fn initiate(api: &Api, key: &str, content_type: Option<&str>) -> Result<String> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct InitiateMultipartUploadResult {
+        upload_id: String,
+    }
+
+    if *api.service() == ServiceType::Azblob {
+        // Blocks are staged under ids derived from this value.
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+        let raw = format!("{key}{}", now.as_nanos());
+        return Ok(format!("{:x}", Sha256::digest(raw.as_bytes()))[..16].to_string());
+    }
+
+    let mut req = api.object(Method::POST, key).query("uploads", "");
+    if let Some(content_type) = content_type {
+        req = req.header("content-type", content_type)?;
+    }
+    let resp = req.send().await?;
+    let result: InitiateMultipartUploadResult = resp.xml()?;
+
+    Ok(result.upload_id)
+}
+
+async fn upload_part(
+    api: &Api,
+    key: &str,
+    upload_id: &str,
+    number: u64,
+    data: Vec<u8>,
+) -> Result<String> {
+    if *api.service() == ServiceType::Azblob {
+        let block_id = azure_block_id(upload_id, number);
+        api.object(Method::PUT, key)
+            .query("comp", "block")
+            .query("blockid", &block_id)
+            .body(data)
+            .send()
+            .await?;
+        return Ok(block_id);
+    }
+
+    let resp = api
+        .object(Method::PUT, key)
+        .query("partNumber", number.to_string())
+        .query("uploadId", upload_id)
+        .body(data)
+        .send()
+        .await?;
+
+    resp.header("etag")
+        .map(str::to_string)
+        .with_context(|| format!("part {number} of `{key}` has no etag"))
+}
+
+async fn complete(api: &Api, checkpoint: &UploadCheckpoint) -> Result<()> {
+    if *api.service() == ServiceType::Azblob {
+        let mut body = String::from(r#"<?xml version="1.0" encoding="utf-8"?><BlockList>"#);
+        for part in checkpoint.parts.iter() {
+            body.push_str(&format!("<Latest>{}</Latest>", part.etag));
+        }
+        body.push_str("</BlockList>");
+        let mut req = api
+            .object(Method::PUT, &checkpoint.key)
+            .query("comp", "blocklist");
+        if let Some(content_type) = &checkpoint.content_type {
+            req = req.header("x-ms-blob-content-type", content_type)?;
+        }
+        req.body(body).send().await?;
+        return Ok(());
+    }
+
+    let mut body = String::from("<CompleteMultipartUpload>");
+    for part in checkpoint.parts.iter() {
+        body.push_str(&format!(
+            "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+            part.number, part.etag
+        ));
+    }
+    body.push_str("</CompleteMultipartUpload>");
+    let resp = api
+        .object(Method::POST, &checkpoint.key)
+        .query("uploadId", &checkpoint.upload_id)
+        .body(body)
+        .send()
+        .await?;
+    // S3 may report a failed completion with a 200 status.
+    if resp.body.windows(7).any(|w| w == b"<Error>") {
+        let text = String::from_utf8_lossy(&resp.body).to_string();
+        return Err(OSSError::WithDescription(text).into());
+    }
+
+    Ok(())
+}
+
+fn azure_block_id(upload_id: &str, number: u64) -> String {
+    base64::engine::general_purpose::STANDARD.encode(format!("{upload_id}-{number:06}"))
+}
+
+fn is_no_such_upload(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<OSSError>(),
+        Some(OSSError::ServiceError(404, ..))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(path: &Path, part_size: u64) -> UploadCheckpoint {
+        let (file_size, modified) = file_stamp(path).unwrap();
+        UploadCheckpoint {
+            key: "a.bin".into(),
+            upload_id: "id".into(),
+            file_size,
+            modified,
+            part_size,
+            parts: vec![],
+            content_type: None,
+        }
+    }
+
+    #[test]
+    fn last_part_is_shorter() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![0; 12]).unwrap();
+
+        let checkpoint = checkpoint(&path, 5);
+        assert_eq!(checkpoint.total_parts(), 3);
+        assert_eq!(checkpoint.part_len(1), 5);
+        assert_eq!(checkpoint.part_len(3), 2);
+        assert_eq!(part_size(1, 12), MIN_PART_SIZE);
+        assert_eq!(
+            part_size(1, MAX_PARTS * MIN_PART_SIZE * 2),
+            MIN_PART_SIZE * 2
+        );
+    }
+
+    #[test]
+    fn checkpoint_resumes_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![0; 12]).unwrap();
+
+        let mut checkpoint = checkpoint(&path, 5);
+        checkpoint.parts.push(CompletedPart {
+            number: 3,
+            etag: "\"c\"".into(),
+        });
+        let saved = dir.path().join("checkpoint");
+        std::fs::write(&saved, serde_json::to_vec(&checkpoint).unwrap()).unwrap();
+
+        let restored: UploadCheckpoint =
+            serde_json::from_slice(&std::fs::read(&saved).unwrap()).unwrap();
+        let (file_size, modified) = file_stamp(&path).unwrap();
+        assert!(restored.matches(file_size, modified));
+        assert_eq!(restored.pending(), vec![1, 2]);
+        assert_eq!(restored.sent(), 2);
+
+        std::fs::write(&path, vec![0; 13]).unwrap();
+        let (file_size, modified) = file_stamp(&path).unwrap();
+        assert!(!restored.matches(file_size, modified));
+    }
+}
@@ -1,44 +1,105 @@
 use crate::Result;
 use anyhow::Context;
+use serde::{Deserialize, Serialize};
 use std::{
     ffi::OsString,
-    fs::{self, File},
+    fs::{self, File, OpenOptions},
     io::{BufWriter, Write},
     path::{Path, PathBuf},
-    time::SystemTime,
 };
 
+/// Remote object version a partial file was started from.
+///
+/// Stored next to the partial file so an interrupted download can be resumed
+/// after a restart, as long as the object did not change in between.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PartialMeta {
+    pub etag: Option<String>,
+    pub size: u64,
+}
+
 /// Small helper struct to make writing files a bit safer by first writing to a
 /// hidden file and once finished renaming it to the requested name.
+///
+/// Unfinished partial files are kept on drop, see [`PartialFile::resume`].
 #[derive(Debug)]
 pub struct PartialFile {
     target_path: PathBuf,
     partial_path: PathBuf,
     partial_file: BufWriter<File>,
-    finished: bool,
+    written: u64,
 }
 
 impl PartialFile {
-    pub fn create(target_path: impl Into<PathBuf>) -> Result<Self> {
+    /// Reopen the partial file left behind by an earlier attempt to download
+    /// `meta` into `target_path`.
+    ///
+    /// If there is none, or it was written for another version of the object,
+    /// start again from scratch.
+    pub fn resume(target_path: impl Into<PathBuf>, meta: PartialMeta) -> Result<Self> {
         let target_path: PathBuf = target_path.into();
         let partial_path = generate_partial_file_name(&target_path)
             .context("could not generate name for partial/temporary file")?;
-        let partial_file = File::create(&partial_path).with_context(|| {
-            format!(
-                "could not create partial/temp file `{}`",
-                partial_path.display()
-            )
-        })?;
-        let partial_file = BufWriter::new(partial_file);
+        let meta_path = generate_meta_file_name(&partial_path);
+
+        let written = fs::metadata(&partial_path).map(|m| m.len()).unwrap_or(0);
+        let previous = fs::read(&meta_path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<PartialMeta>(&raw).ok());
+        let resumable = meta.etag.is_some()
+            && previous.as_ref() == Some(&meta)
+            && written > 0
+            && written <= meta.size;
+
+        let (partial_file, written) = if resumable {
+            tracing::info!(
+                "Resuming partial file `{}` at byte {}.",
+                partial_path.display(),
+                written
+            );
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&partial_path)
+                .with_context(|| {
+                    format!(
+                        "could not open partial/temp file `{}`",
+                        partial_path.display()
+                    )
+                })?;
+            (file, written)
+        } else {
+            if previous.is_some() {
+                tracing::info!(
+                    "Remote object changed, restarting partial file `{}`.",
+                    partial_path.display()
+                );
+            }
+            let file = File::create(&partial_path).with_context(|| {
+                format!(
+                    "could not create partial/temp file `{}`",
+                    partial_path.display()
+                )
+            })?;
+            let raw = serde_json::to_vec(&meta).context("serialize partial file meta")?;
+            fs::write(&meta_path, raw).with_context(|| {
+                format!("could not write partial meta `{}`", meta_path.display())
+            })?;
+            (file, 0)
+        };
 
         Ok(PartialFile {
             target_path,
             partial_path,
-            partial_file,
-            finished: false,
+            partial_file: BufWriter::new(partial_file),
+            written,
         })
     }
 
+    /// Number of bytes already in the partial file when it was opened.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
     pub fn finish(mut self) -> Result<File> {
         self.partial_file.flush().with_context(|| {
             format!(
@@ -53,7 +114,14 @@ impl PartialFile {
                 self.target_path.display()
             )
         })?;
-        self.finished = true;
+        let meta_path = generate_meta_file_name(&self.partial_path);
+        if let Err(e) = fs::remove_file(&meta_path) {
+            tracing::warn!(
+                "Could not delete partial meta `{}`: {}",
+                meta_path.display(),
+                e
+            )
+        }
         File::open(&self.target_path)
             .with_context(|| format!("cannot open finished file `{}`", self.target_path.display()))
     }
@@ -79,19 +147,17 @@ impl Write for PartialFile {
 
 impl Drop for PartialFile {
     fn drop(&mut self) {
-        if self.finished {
+        if !self.partial_path.exists() {
             return;
         }
 
-        tracing::info!("Deleting partial file `{}`.", self.partial_path.display());
-        tracing::debug!(
-            "Partial file `{}` was meant to be moved to `{}` once finished",
-            self.partial_path.display(),
-            self.target_path.display()
+        tracing::info!(
+            "Keeping partial file `{}` to resume later.",
+            self.partial_path.display()
         );
-        if let Err(e) = fs::remove_file(&self.partial_path) {
+        if let Err(e) = self.partial_file.flush() {
             tracing::warn!(
-                "Could not delete partial file `{}`: {}",
+                "Could not flush partial file `{}`: {}",
                 self.partial_path.display(),
                 e
             )
@@ -103,18 +169,74 @@ fn generate_partial_file_name(path: &Path) -> Result<PathBuf> {
     let target_file_name = path
         .file_name()
         .with_context(|| format!("cannot get file name from path `{}`", path.display()))?;
-    let temp_prefix = {
-        let timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .context("cannot get timestamp")?;
-        format!("artefacta-temp-{}", timestamp.as_secs())
-    };
     let new_file_name = {
-        let mut res = OsString::from("._");
-        res.push(&temp_prefix);
+        let mut res = OsString::from("._artefacta-temp-");
         res.push(target_file_name);
         res.push(".part");
         res
     };
     Ok(path.with_file_name(new_file_name))
 }
+
+fn generate_meta_file_name(partial_path: &Path) -> PathBuf {
+    let mut res = partial_path.as_os_str().to_owned();
+    res.push(".meta");
+    PathBuf::from(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(etag: Option<&str>, size: u64) -> PartialMeta {
+        PartialMeta {
+            etag: etag.map(str::to_string),
+            size,
+        }
+    }
+
+    /// Write `data` and drop the file unfinished, as an interrupted download.
+    fn interrupt(target: &Path, meta: PartialMeta, data: &[u8]) {
+        let mut file = PartialFile::resume(target, meta).unwrap();
+        file.write_all(data).unwrap();
+    }
+
+    #[test]
+    fn resume_appends_to_same_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("a.bin");
+        interrupt(&target, meta(Some("\"v1\""), 6), b"abc");
+
+        let mut file = PartialFile::resume(&target, meta(Some("\"v1\""), 6)).unwrap();
+        assert_eq!(file.written(), 3);
+        file.write_all(b"def").unwrap();
+        file.finish().unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"abcdef");
+        let partial = generate_partial_file_name(&target).unwrap();
+        assert!(!partial.exists());
+        assert!(!generate_meta_file_name(&partial).exists());
+    }
+
+    #[test]
+    fn resume_restarts_on_changed_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("a.bin");
+        interrupt(&target, meta(Some("\"v1\""), 6), b"abc");
+
+        let mut file = PartialFile::resume(&target, meta(Some("\"v2\""), 6)).unwrap();
+        assert_eq!(file.written(), 0);
+        file.write_all(b"uvwxyz").unwrap();
+        file.finish().unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"uvwxyz");
+    }
+
+    #[test]
+    fn resume_restarts_without_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("a.bin");
+        interrupt(&target, meta(None, 6), b"abc");
+
+        let file = PartialFile::resume(&target, meta(None, 6)).unwrap();
+        assert_eq!(file.written(), 0);
+    }
+}
@@ -0,0 +1,152 @@
+//! Browser POST upload policies of S3 (signature V4) and OSS, signed with the
+//! credentials of the session. Uploaders send the fields and the file as a
+//! `multipart/form-data` form, without any credentials of their own.
+use crate::api::Api;
+use crate::error::OSSError;
+use crate::Result;
+use base64::Engine;
+use cc_core::ServiceType;
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::Sha256;
+
+pub(crate) fn is_supported(service: &ServiceType) -> bool {
+    matches!(
+        service,
+        ServiceType::S3 | ServiceType::S3Compatible | ServiceType::Oss
+    )
+}
+
+/// Conditions of the uploads allowed by a policy.
+#[derive(Clone, Debug, Default)]
+pub struct PostPolicyParams {
+    /// Uploaded keys must start with it, the name of the file is appended.
+    pub key_prefix: String,
+    /// Seconds the policy stays valid.
+    pub expire: u64,
+    /// Accepted sizes in bytes, any size when `None`.
+    pub size_range: Option<(u64, u64)>,
+    /// Exact Content-Type, or a prefix when it ends with `/`, e.g. `image/`.
+    pub content_type: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PostPolicy {
+    /// Where the form is posted.
+    pub url: String,
+    /// Form fields, sent before the `file` field.
+    pub fields: Vec<(String, String)>,
+}
+
+impl PostPolicy {
+    pub fn to_json(&self) -> String {
+        let fields: serde_json::Map<_, _> = self
+            .fields
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        serde_json::to_string_pretty(&json!({ "url": self.url, "fields": fields }))
+            .unwrap_or_default()
+    }
+
+    /// A `curl` command uploading `file`.
+    pub fn to_curl(&self, file: &str) -> String {
+        let mut cmd = String::from("curl");
+        for (k, v) in &self.fields {
+            cmd.push_str(&format!(" \\\n  -F '{k}={v}'"));
+        }
+        cmd.push_str(&format!(" \\\n  -F 'file=@{file}' \\\n  '{}'", self.url));
+        cmd
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub(crate) fn sign(
+    api: &Api,
+    access_key_id: &str,
+    secret: &str,
+    params: &PostPolicyParams,
+) -> Result<PostPolicy> {
+    if !is_supported(api.service()) {
+        return Err(OSSError::WithDescription(
+            "POST policies are only supported by S3 and OSS".into(),
+        )
+        .into());
+    }
+    let now = Utc::now();
+    let expiration = now + Duration::seconds(params.expire as i64);
+
+    let mut fields = vec![(
+        "key".to_string(),
+        format!("{}${{filename}}", params.key_prefix),
+    )];
+    let mut conditions = vec![
+        json!({ "bucket": api.bucket_name() }),
+        json!(["starts-with", "$key", params.key_prefix]),
+    ];
+    if let Some((min, max)) = params.size_range {
+        conditions.push(json!(["content-length-range", min, max]));
+    }
+    if let Some(content_type) = params.content_type.as_ref().filter(|t| !t.is_empty()) {
+        if content_type.ends_with('/') {
+            conditions.push(json!(["starts-with", "$Content-Type", content_type]));
+        } else {
+            conditions.push(json!({ "Content-Type": content_type }));
+        }
+        fields.push(("Content-Type".into(), content_type.clone()));
+    }
+
+    let encode = |policy: Value| {
+        base64::engine::general_purpose::STANDARD.encode(policy.to_string().as_bytes())
+    };
+    if *api.service() == ServiceType::Oss {
+        let policy = encode(json!({
+            "expiration": expiration.format("%Y-%m-%dT%H:%M:%S.000Z").to_string(),
+            "conditions": conditions,
+        }));
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+        mac.update(policy.as_bytes());
+        let signature =
+            base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        fields.push(("OSSAccessKeyId".into(), access_key_id.to_string()));
+        fields.push(("policy".into(), policy));
+        fields.push(("Signature".into(), signature));
+    } else {
+        let date = now.format("%Y%m%d").to_string();
+        let credential = format!("{access_key_id}/{date}/{}/s3/aws4_request", api.region());
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        conditions.push(json!({ "x-amz-algorithm": "AWS4-HMAC-SHA256" }));
+        conditions.push(json!({ "x-amz-credential": credential }));
+        conditions.push(json!({ "x-amz-date": amz_date }));
+        let policy = encode(json!({
+            "expiration": expiration.format("%Y-%m-%dT%H:%M:%S.000Z").to_string(),
+            "conditions": conditions,
+        }));
+        let key = [date.as_str(), api.region(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{secret}").into_bytes(), |key, part| {
+                hmac_sha256(&key, part)
+            });
+        let signature = hex::encode(hmac_sha256(&key, &policy));
+        fields.push(("x-amz-algorithm".into(), "AWS4-HMAC-SHA256".into()));
+        fields.push(("x-amz-credential".into(), credential));
+        fields.push(("x-amz-date".into(), amz_date));
+        fields.push(("policy".into(), policy));
+        fields.push(("x-amz-signature".into(), signature));
+    }
+
+    Ok(PostPolicy {
+        url: format!("{}/", api.bucket_url()),
+        fields,
+    })
+}
@@ -0,0 +1,191 @@
+//! Archived objects (S3 Glacier, OSS Archive, Azure Archive tier) must be
+//! restored before they can be read. Google Cloud Storage has no such tier.
+use crate::api::{Api, ApiResponse};
+use crate::meta::storage_class_header;
+use crate::Result;
+use cc_core::ServiceType;
+use http::Method;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum RestoreState {
+    /// Not archived, readable at any time.
+    #[default]
+    Online,
+    Archived,
+    Restoring,
+    /// A readable copy, until the expiry date when the service gives one.
+    Restored(Option<String>),
+}
+
+impl RestoreState {
+    pub fn is_readable(&self) -> bool {
+        matches!(self, RestoreState::Online | RestoreState::Restored(_))
+    }
+
+    /// The object is archived, restored or not.
+    pub fn is_archived(&self) -> bool {
+        *self != RestoreState::Online
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            RestoreState::Online => "Online".into(),
+            RestoreState::Archived => "Archived, restore it to read the content".into(),
+            RestoreState::Restoring => "Restore in progress".into(),
+            RestoreState::Restored(Some(expiry)) => format!("Restored until {expiry}"),
+            RestoreState::Restored(None) => "Restored".into(),
+        }
+    }
+}
+
+/// Storage classes whose objects must be restored before a read.
+pub fn is_archive_class(service: &ServiceType, class: &str) -> bool {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible => {
+            matches!(class, "GLACIER" | "DEEP_ARCHIVE")
+        }
+        ServiceType::Oss => matches!(class, "Archive" | "ColdArchive" | "DeepColdArchive"),
+        ServiceType::Gcs => false,
+        ServiceType::Azblob => class == "Archive",
+    }
+}
+
+/// Retrieval tiers of a restore, from the fastest, empty when restores are
+/// not supported. Azure calls them rehydrate priorities.
+pub fn tiers(service: &ServiceType) -> &'static [&'static str] {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible | ServiceType::Oss => {
+            &["Expedited", "Standard", "Bulk"]
+        }
+        ServiceType::Gcs => &[],
+        ServiceType::Azblob => &["High", "Standard"],
+    }
+}
+
+/// Retrieval tiers of a restore of objects of `class`, all of `tiers` when
+/// the class takes any or is not known.
+pub fn class_tiers(service: &ServiceType, class: &str) -> &'static [&'static str] {
+    match (service, class) {
+        // Intelligent-Tiering archive access tiers have no expedited retrieval.
+        (ServiceType::S3 | ServiceType::S3Compatible, "DEEP_ARCHIVE" | "INTELLIGENT_TIERING") => {
+            &["Standard", "Bulk"]
+        }
+        (ServiceType::Oss, "DeepColdArchive") => &["Standard", "Bulk"],
+        _ => tiers(service),
+    }
+}
+
+/// Read the state from the headers of a HEAD request on the object.
+pub(crate) fn state(service: &ServiceType, resp: &ApiResponse) -> RestoreState {
+    let class = resp
+        .header(storage_class_header(service))
+        .unwrap_or_default();
+    match service {
+        ServiceType::Gcs => RestoreState::Online,
+        ServiceType::Azblob => {
+            if class != "Archive" {
+                RestoreState::Online
+            } else if resp
+                .header("x-ms-archive-status")
+                .is_some_and(|s| s.starts_with("rehydrate-pending"))
+            {
+                RestoreState::Restoring
+            } else {
+                RestoreState::Archived
+            }
+        }
+        _ => {
+            let (restore, archive_status) = match service {
+                ServiceType::Oss => ("x-oss-restore", None),
+                _ => ("x-amz-restore", resp.header("x-amz-archive-status")),
+            };
+            // Intelligent-Tiering objects in an archive access tier.
+            if !is_archive_class(service, class) && archive_status.is_none() {
+                return RestoreState::Online;
+            }
+            match resp.header(restore) {
+                // ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"
+                Some(value) if value.contains(r#"ongoing-request="true""#) => {
+                    RestoreState::Restoring
+                }
+                Some(value) => RestoreState::Restored(
+                    value
+                        .split_once(r#"expiry-date=""#)
+                        .and_then(|(_, date)| date.split('"').next())
+                        .map(str::to_string),
+                ),
+                None => RestoreState::Archived,
+            }
+        }
+    }
+}
+
+pub(crate) async fn head(api: &Api, key: &str) -> Result<ApiResponse> {
+    api.object(Method::HEAD, key).send().await
+}
+
+pub(crate) async fn status(api: &Api, key: &str) -> Result<RestoreState> {
+    let resp = head(api, key).await?;
+    Ok(state(api.service(), &resp))
+}
+
+/// Start a restore of `key` for `days` with the retrieval `tier`, `head` is
+/// the response of a HEAD request on it. Azure moves the blob back to the hot
+/// tier instead, for good.
+pub(crate) async fn restore(
+    api: &Api,
+    key: &str,
+    head: &ApiResponse,
+    days: u32,
+    tier: &str,
+) -> Result<()> {
+    let service = api.service();
+    let class = head
+        .header(storage_class_header(service))
+        .unwrap_or_default();
+    if !class_tiers(service, class).contains(&tier) {
+        anyhow::bail!("{key}: the {tier} tier is not available for {class}");
+    }
+    match service {
+        ServiceType::Azblob => {
+            api.object(Method::PUT, key)
+                .query("comp", "tier")
+                .header("x-ms-access-tier", "Hot")?
+                .header("x-ms-rehydrate-priority", tier)?
+                .send()
+                .await?;
+        }
+        ServiceType::Oss => {
+            // Only Cold Archive objects take a tier, Archive ones refuse it.
+            let job = match class {
+                "Archive" => String::new(),
+                _ => format!("<JobParameters><Tier>{tier}</Tier></JobParameters>"),
+            };
+            let body = format!("<RestoreRequest><Days>{days}</Days>{job}</RestoreRequest>");
+            api.object(Method::POST, key)
+                .query("restore", "")
+                .xml_body(body)?
+                .send()
+                .await?;
+        }
+        _ => {
+            // Intelligent-Tiering objects move back to the frequent access
+            // tier, a restore of them takes no days.
+            let days = match head.header("x-amz-archive-status") {
+                Some(_) => String::new(),
+                None => format!("<Days>{days}</Days>"),
+            };
+            let body = format!(
+                "<RestoreRequest>{days}<GlacierJobParameters>\
+                 <Tier>{tier}</Tier></GlacierJobParameters></RestoreRequest>"
+            );
+            api.object(Method::POST, key)
+                .query("restore", "")
+                .xml_body(body)?
+                .send()
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use crate::filter::Filter;
+use crate::types::Object;
+use crate::Result;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Criteria of a recursive search, unset criteria match everything.
+#[derive(Clone, Debug, Default)]
+pub struct SearchQuery {
+    /// Folder to search, the whole bucket when empty.
+    pub prefix: String,
+    /// Matched against the file name.
+    pub filter: Option<Filter>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<DateTime<Utc>>,
+    pub modified_before: Option<DateTime<Utc>>,
+}
+
+impl SearchQuery {
+    pub fn matches(&self, obj: &Object) -> bool {
+        let size = obj.size() as u64;
+        if self.min_size.is_some_and(|min| size < min)
+            || self.max_size.is_some_and(|max| size > max)
+        {
+            return false;
+        }
+        if self.modified_after.is_some() || self.modified_before.is_some() {
+            let Some(date) = obj.last_modified() else {
+                return false;
+            };
+            if self.modified_after.is_some_and(|after| date < after)
+                || self.modified_before.is_some_and(|before| date > before)
+            {
+                return false;
+            }
+        }
+        match &self.filter {
+            Some(filter) => filter.find(&obj.name()).is_some(),
+            None => true,
+        }
+    }
+
+    /// Parse sizes like `1.5 MiB` or `200kb`, empty input means no limit.
+    pub fn parse_size(text: &str) -> Result<Option<u64>> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let size = text
+            .parse::<bytesize::ByteSize>()
+            .map_err(|err| anyhow::anyhow!("invalid size `{text}`: {err}"))?;
+
+        Ok(Some(size.as_u64()))
+    }
+
+    /// Parse dates like `2024-01-31`, empty input means no limit.
+    ///
+    /// With `end_of_day` the date includes the whole day.
+    pub fn parse_date(text: &str, end_of_day: bool) -> Result<Option<DateTime<Utc>>> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let date = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .with_context(|| format!("invalid date `{text}`, expected YYYY-MM-DD"))?;
+        let time = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+
+        Ok(time.map(|t| t.and_utc()))
+    }
+}
+
+/// Stops a running search when cancelled, clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,48 @@
+//! Storage classes (access tiers on Azure) of objects. opendal does not list
+//! them, they come from the REST listing of each service, see `listing`.
+use crate::api::Api;
+use crate::meta::{self, storage_class_header};
+use crate::Result;
+use cc_core::ServiceType;
+use http::Method;
+
+/// Classes an object can be moved to, from the most to the least available.
+pub fn classes(service: &ServiceType) -> &'static [&'static str] {
+    match service {
+        ServiceType::S3 | ServiceType::S3Compatible => &[
+            "STANDARD",
+            "INTELLIGENT_TIERING",
+            "STANDARD_IA",
+            "ONEZONE_IA",
+            "GLACIER_IR",
+            "GLACIER",
+            "DEEP_ARCHIVE",
+        ],
+        ServiceType::Oss => &[
+            "Standard",
+            "IA",
+            "Archive",
+            "ColdArchive",
+            "DeepColdArchive",
+        ],
+        ServiceType::Gcs => &["STANDARD", "NEARLINE", "COLDLINE", "ARCHIVE"],
+        ServiceType::Azblob => &["Hot", "Cool", "Cold", "Archive"],
+    }
+}
+
+/// Move `key` to `class`, by copying the object onto itself with its metadata
+/// kept, or by setting the tier on Azure.
+pub(crate) async fn set(api: &Api, key: &str, class: &str) -> Result<()> {
+    if *api.service() == ServiceType::Azblob {
+        api.object(Method::PUT, key)
+            .query("comp", "tier")
+            .header(storage_class_header(api.service()), class)?
+            .send()
+            .await?;
+        return Ok(());
+    }
+
+    let mut meta = meta::get(api, key).await?;
+    meta.storage_class = Some(class.to_string());
+    meta::set(api, key, &meta).await
+}
@@ -12,9 +12,9 @@ use tokio::{fs::File, io::AsyncReadExt};
 
 use crate::error::ObjectResult;
 use bytes::Bytes;
-use opendal::Operator;
+use opendal::{Operator, Writer};
 
-const DEFAULT_BUFFER_SIZE: usize = 2048;
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
 
 /// The callback function triggered every time a chunck of the source file is read
 /// in the buffer.
@@ -110,28 +110,49 @@ impl<I: AsyncReadExt + Unpin> Stream for TrackableBodyStream<I> {
 
 pub type BoxedStreamingUploader = Box<StreamingUploader>;
 
-/// Store multiple parts in a map, and concatenate them on finish.
+/// Send parts to the backend as soon as they are filled, using the service's
+/// multipart upload (initiate, upload part, complete, abort).
 pub struct StreamingUploader {
-    op: Operator,
     path: String,
-    buffer: Vec<Bytes>,
+    writer: Writer,
 }
+
 impl StreamingUploader {
-    pub fn new(op: Operator, path: String) -> Self {
-        Self {
-            op,
-            path,
-            buffer: vec![],
+    pub async fn new(
+        op: &Operator,
+        path: String,
+        part_size: usize,
+        concurrency: usize,
+        content_type: Option<&str>,
+    ) -> ObjectResult<Self> {
+        let mut writer = op
+            .writer_with(&path)
+            .chunk(part_size)
+            .concurrent(concurrency);
+        if let Some(content_type) = content_type {
+            writer = writer.content_type(content_type);
         }
+        let writer = writer.await?;
+
+        Ok(Self { path, writer })
     }
 
     pub async fn write_bytes(&mut self, data: Bytes) -> ObjectResult<()> {
-        self.buffer.push(data);
+        self.writer.write(data).await?;
+        Ok(())
+    }
+
+    /// Complete the upload, the object is only visible after this succeeds.
+    pub async fn finish(mut self: Box<Self>) -> ObjectResult<()> {
+        self.writer.close().await?;
+
         Ok(())
     }
 
-    pub async fn finish(self: Box<Self>) -> ObjectResult<()> {
-        self.op.write(&self.path, self.buffer).await?;
+    /// Abort the upload and drop the parts that were already sent.
+    pub async fn abort(mut self: Box<Self>) -> ObjectResult<()> {
+        tracing::debug!("Abort upload: {}", self.path);
+        self.writer.abort().await?;
 
         Ok(())
     }
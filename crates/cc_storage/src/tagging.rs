@@ -0,0 +1,148 @@
+//! Object tags of S3, OSS and the blob index tags of Azure, Google Cloud
+//! Storage has no equivalent.
+use crate::api::{Api, ApiRequest};
+use crate::error::OSSError;
+use crate::Result;
+use cc_core::ServiceType;
+use http::Method;
+use quick_xml::escape::escape;
+use serde::Deserialize;
+
+/// Key and value pairs, in the order returned by the service.
+pub type Tags = Vec<(String, String)>;
+
+/// Changes applied to the tags of each object of a selection.
+#[derive(Clone, Debug, Default)]
+pub struct TagPatch {
+    pub set: Tags,
+    pub remove: Vec<String>,
+    /// Replace all the tags with `set`.
+    pub replace: bool,
+}
+
+impl TagPatch {
+    pub fn apply(&self, tags: &mut Tags) {
+        if self.replace {
+            tags.clear();
+        }
+        tags.retain(|(k, _)| !self.remove.contains(k));
+        for (key, value) in &self.set {
+            let key = key.trim();
+            if key.is_empty() {
+                continue;
+            }
+            match tags.iter_mut().find(|(k, _)| k == key) {
+                Some((_, v)) => *v = value.clone(),
+                None => tags.push((key.to_string(), value.clone())),
+            }
+        }
+    }
+}
+
+/// A `key` or `key=value` condition on the tags of an object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagFilter {
+    key: String,
+    value: Option<String>,
+}
+
+impl TagFilter {
+    /// `None` for empty input.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(match text.split_once('=') {
+            Some((key, value)) => TagFilter {
+                key: key.trim().to_string(),
+                value: Some(value.trim().to_string()),
+            },
+            None => TagFilter {
+                key: text.to_string(),
+                value: None,
+            },
+        })
+    }
+
+    pub fn matches(&self, tags: &Tags) -> bool {
+        tags.iter()
+            .any(|(k, v)| *k == self.key && self.value.as_ref().is_none_or(|value| v == value))
+    }
+}
+
+pub(crate) fn is_supported(service: &ServiceType) -> bool {
+    *service != ServiceType::Gcs
+}
+
+fn tagging(api: &Api, method: Method, key: &str) -> Result<ApiRequest> {
+    let req = api.object(method, key);
+    Ok(match api.service() {
+        ServiceType::Azblob => req.query("comp", "tags"),
+        ServiceType::Gcs => {
+            return Err(OSSError::WithDescription(
+                "Google Cloud Storage does not support object tags".into(),
+            )
+            .into())
+        }
+        _ => req.query("tagging", ""),
+    })
+}
+
+pub(crate) async fn get(api: &Api, key: &str) -> Result<Tags> {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Tagging {
+        tag_set: TagSet,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct TagSet {
+        #[serde(rename = "Tag")]
+        tags: Vec<Tag>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Tag {
+        key: String,
+        value: String,
+    }
+
+    let tagging: Tagging = tagging(api, Method::GET, key)?.send().await?.xml()?;
+
+    Ok(tagging
+        .tag_set
+        .tags
+        .into_iter()
+        .map(|t| (t.key, t.value))
+        .collect())
+}
+
+/// Replace the tags of `key`, an empty set removes them.
+pub(crate) async fn put(api: &Api, key: &str, tags: &Tags) -> Result<()> {
+    if tags.is_empty() && *api.service() != ServiceType::Azblob {
+        tagging(api, Method::DELETE, key)?.send().await?;
+        return Ok(());
+    }
+
+    // Azure names the root `Tags`, S3 and OSS `Tagging`.
+    let root = match api.service() {
+        ServiceType::Azblob => "Tags",
+        _ => "Tagging",
+    };
+    let mut body = format!(r#"<?xml version="1.0" encoding="UTF-8"?><{root}><TagSet>"#);
+    for (k, v) in tags {
+        body.push_str(&format!(
+            "<Tag><Key>{}</Key><Value>{}</Value></Tag>",
+            escape(k.as_str()),
+            escape(v.as_str())
+        ));
+    }
+    body.push_str(&format!("</TagSet></{root}>"));
+    tagging(api, Method::PUT, key)?
+        .xml_body(body)?
+        .send()
+        .await?;
+
+    Ok(())
+}
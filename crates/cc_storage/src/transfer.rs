@@ -19,6 +19,8 @@ impl TransferProgressInfo {
 pub enum TransferType {
     Download(String, TransferProgressInfo),
     Upload(String, TransferProgressInfo),
+    /// Progress of a batch operation like delete or copy, counted in keys.
+    Task(String, TransferProgressInfo),
 }
 
 pub struct TransferManager {
@@ -26,6 +28,7 @@ pub struct TransferManager {
     pub t_type: String,
     downloads: BTreeMap<String, TransferProgressInfo>,
     uploads: BTreeMap<String, TransferProgressInfo>,
+    tasks: BTreeMap<String, TransferProgressInfo>,
     pub filter: String,
     pub progress_tx: crossbeam_channel::Sender<TransferType>,
     pub progress_rx: crossbeam_channel::Receiver<TransferType>,
@@ -45,6 +48,7 @@ impl TransferManager {
             t_type: "download".into(),
             downloads: BTreeMap::new(),
             uploads: BTreeMap::new(),
+            tasks: BTreeMap::new(),
             filter: String::new(),
             progress_tx,
             progress_rx,
@@ -52,10 +56,10 @@ impl TransferManager {
     }
 
     pub fn data(&self) -> &BTreeMap<String, TransferProgressInfo> {
-        if self.is_upload() {
-            &self.uploads
-        } else {
-            &self.downloads
+        match self.t_type.as_str() {
+            "upload" => &self.uploads,
+            "task" => &self.tasks,
+            _ => &self.downloads,
         }
     }
 
@@ -64,7 +68,7 @@ impl TransferManager {
     }
 
     pub fn total(&self) -> usize {
-        self.downloads.len() + self.uploads.len()
+        self.downloads.len() + self.uploads.len() + self.tasks.len()
     }
 
     pub fn show(&mut self, t_type: &str) {
@@ -87,6 +91,15 @@ impl TransferManager {
                     self.update_download(key, item);
                     repaint();
                 }
+                TransferType::Task(key, item) => {
+                    tracing::debug!(
+                        "Task `{key}`… {}/{}",
+                        item.transferred_bytes,
+                        item.total_bytes
+                    );
+                    self.tasks.insert(key, item);
+                    repaint();
+                }
             }
         }
     }
@@ -2,6 +2,8 @@ use crate::util::get_name_form_path;
 use chrono::{DateTime, Utc};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 pub type Params = BTreeMap<String, Option<String>>;
 pub type Headers = HashMap<String, String>;
@@ -15,6 +17,7 @@ pub struct ListObjects {
     max_keys: String,
     is_truncated: bool,
     next_continuation_token: Option<String>,
+    generation: u64,
 
     pub objects: Vec<Object>,
     pub common_prefixes: Vec<Object>,
@@ -80,6 +83,15 @@ impl ListObjects {
         self.next_continuation_token = next_continuation_token;
     }
 
+    /// `ListObjectsV2Params::generation` of the query of this page.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+
     pub fn set_objects(&mut self, objects: Vec<Object>) {
         self.objects = objects;
     }
@@ -203,6 +215,9 @@ impl Object {
     pub fn set_url(&mut self, url: String) {
         self.url = url;
     }
+    pub fn set_storage_class(&mut self, storage_class: String) {
+        self.storage_class = storage_class;
+    }
     pub fn set_mine_type(&mut self, mine_type: String) {
         self.mine_type = mine_type;
     }
@@ -216,15 +231,55 @@ pub enum BucketACL {
     Private,
 }
 
+impl BucketACL {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BucketACL::PublicReadWrite => "public-read-write",
+            BucketACL::PublicRead => "public-read",
+            BucketACL::Private => "private",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Versioning {
+    /// Not reported by the service or not allowed to read it.
+    #[default]
+    Unknown,
+    Disabled,
+    Enabled,
+    Suspended,
+}
+
+impl Versioning {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Versioning::Unknown => "Unknown",
+            Versioning::Disabled => "Disabled",
+            Versioning::Enabled => "Enabled",
+            Versioning::Suspended => "Suspended",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Bucket {
     name: String,
     grant: BucketACL,
+    pub region: Option<String>,
+    pub creation_date: Option<DateTime<Utc>>,
+    pub versioning: Versioning,
+    /// Details that could not be read, e.g. for lack of permission.
+    pub warnings: Vec<String>,
 }
 
 impl Bucket {
     pub fn new(name: String, grant: BucketACL) -> Self {
-        Bucket { name, grant }
+        Bucket {
+            name,
+            grant,
+            ..Default::default()
+        }
     }
 
     pub fn name(&self) -> &str {
@@ -235,6 +290,10 @@ impl Bucket {
         &self.grant
     }
 
+    pub fn set_grant(&mut self, grant: BucketACL) {
+        self.grant = grant;
+    }
+
     pub fn is_private(&self) -> bool {
         self.grant == BucketACL::Private
     }
@@ -254,6 +313,13 @@ pub struct ListObjectsV2Params {
     pub prefix: String,
     pub start_after: String,
     pub is_truncated: bool,
+    /// Page size, `0` lists everything at once.
+    pub max_keys: usize,
+    /// List the objects of all subfolders instead of a single level.
+    pub recursive: bool,
+    /// Bumped for every new listing, pages of an older one are stale.
+    pub generation: u64,
+    pub cursor: ListCursor,
 }
 
 impl ListObjectsV2Params {
@@ -262,6 +328,57 @@ impl ListObjectsV2Params {
             prefix,
             start_after,
             is_truncated,
+            ..Default::default()
         }
     }
 }
+
+#[derive(Default)]
+pub(crate) struct ListCursorInner {
+    /// Continuation token of the next page.
+    pub(crate) token: Option<String>,
+    /// The last page was listed.
+    pub(crate) done: bool,
+}
+
+/// Keeps the position of a listing between pages, so the next page
+/// continues exactly where the last one stopped.
+///
+/// Clones share the same position.
+#[derive(Clone, Default)]
+pub struct ListCursor {
+    pub(crate) inner: Arc<Mutex<ListCursorInner>>,
+}
+
+impl Debug for ListCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ListCursor").finish_non_exhaustive()
+    }
+}
+
+/// Outcome of a batch operation for a single key.
+#[derive(Clone, Debug)]
+pub struct BatchItem {
+    pub key: String,
+    pub error: Option<String>,
+}
+
+impl BatchItem {
+    pub fn ok(key: impl Into<String>) -> Self {
+        BatchItem {
+            key: key.into(),
+            error: None,
+        }
+    }
+
+    pub fn err(key: impl Into<String>, error: impl ToString) -> Self {
+        BatchItem {
+            key: key.into(),
+            error: Some(error.to_string()),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
@@ -1,5 +1,5 @@
 use std::ffi::OsStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn get_name(path: &Path) -> String {
     path.file_name()
@@ -15,3 +15,53 @@ pub fn get_name_form_path(path: &str) -> String {
         .unwrap_or("")
         .to_string()
 }
+
+/// All files below `dir` and the subfolders without any, recursively and in a
+/// stable order.
+///
+/// Symlinked folders are skipped, they may point back to a parent and
+/// never end. Symlinked files are followed.
+pub fn walk_dir(dir: &Path) -> std::io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let (mut files, mut empty_dirs) = (vec![], vec![]);
+    let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.path());
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let (sub_files, sub_dirs) = walk_dir(&path)?;
+            if sub_files.is_empty() && sub_dirs.is_empty() {
+                empty_dirs.push(path);
+            } else {
+                files.extend(sub_files);
+                empty_dirs.extend(sub_dirs);
+            }
+        } else if file_type.is_symlink() && path.is_dir() {
+            tracing::warn!("Skip symlinked folder `{}`", path.display());
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok((files, empty_dirs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_dir_skips_symlinked_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("a/empty")).unwrap();
+        std::fs::write(root.join("a/1.txt"), "1").unwrap();
+        std::os::unix::fs::symlink(root, root.join("a/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("a/1.txt"), root.join("2.txt")).unwrap();
+
+        let (files, empty_dirs) = walk_dir(root).unwrap();
+        assert_eq!(files, [root.join("2.txt"), root.join("a/1.txt")]);
+        assert_eq!(empty_dirs, [root.join("a/empty")]);
+    }
+}
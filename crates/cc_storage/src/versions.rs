@@ -0,0 +1,335 @@
+//! Versions and delete markers of objects in versioned buckets. Google Cloud
+//! Storage calls versions generations and Azure has no delete markers.
+use crate::api::{Api, ApiRequest};
+use crate::Result;
+use cc_core::ServiceType;
+use chrono::{DateTime, Utc};
+use http::Method;
+use serde::Deserialize;
+
+/// Listings stop after this many versions.
+pub const MAX_VERSIONS: usize = 5000;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjectVersion {
+    pub key: String,
+    pub version_id: String,
+    pub is_latest: bool,
+    /// The object was deleted, this version has no content.
+    pub is_delete_marker: bool,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub size: u64,
+}
+
+impl ObjectVersion {
+    pub fn date_string(&self) -> String {
+        match self.last_modified {
+            Some(date) => date.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => "_".into(),
+        }
+    }
+
+    pub fn size_string(&self) -> String {
+        if self.is_delete_marker {
+            "-".into()
+        } else {
+            bytesize::ByteSize(self.size).to_string()
+        }
+    }
+}
+
+/// A request on one version of `key`.
+pub(crate) fn versioned(api: &Api, method: Method, key: &str, version_id: &str) -> ApiRequest {
+    let name = match api.service() {
+        ServiceType::Gcs => "generation",
+        ServiceType::Azblob => "versionid",
+        _ => "versionId",
+    };
+    api.object(method, key).query(name, version_id)
+}
+
+/// Versions of the objects below `prefix`, newest first for each key, and
+/// whether the listing stopped at `MAX_VERSIONS`.
+pub(crate) async fn list(api: &Api, prefix: &str) -> Result<(Vec<ObjectVersion>, bool)> {
+    let mut versions = vec![];
+    let mut marker = None;
+    loop {
+        let next = match api.service() {
+            ServiceType::Gcs => list_gcs(api, prefix, marker, &mut versions).await?,
+            ServiceType::Azblob => list_azblob(api, prefix, marker, &mut versions).await?,
+            _ => list_s3(api, prefix, marker, &mut versions).await?,
+        };
+        marker = next;
+        if marker.is_none() {
+            return Ok((versions, false));
+        }
+        if versions.len() >= MAX_VERSIONS {
+            return Ok((versions, true));
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct ListVersionsResult {
+    is_truncated: bool,
+    next_key_marker: String,
+    next_version_id_marker: String,
+    #[serde(rename = "$value")]
+    entries: Vec<VersionEntry>,
+}
+
+/// Versions and delete markers are interleaved in the listing.
+#[derive(Deserialize)]
+enum VersionEntry {
+    Version(S3Version),
+    DeleteMarker(S3Version),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct S3Version {
+    key: String,
+    version_id: String,
+    is_latest: bool,
+    last_modified: String,
+    size: u64,
+}
+
+fn parse_s3(text: &str, versions: &mut Vec<ObjectVersion>) -> Result<Option<(String, String)>> {
+    let result: ListVersionsResult = quick_xml::de::from_str(text)?;
+    for entry in result.entries {
+        let (v, is_delete_marker) = match entry {
+            VersionEntry::Version(v) => (v, false),
+            VersionEntry::DeleteMarker(v) => (v, true),
+            VersionEntry::Other => continue,
+        };
+        versions.push(ObjectVersion {
+            key: v.key,
+            version_id: v.version_id,
+            is_latest: v.is_latest,
+            is_delete_marker,
+            last_modified: DateTime::parse_from_rfc3339(&v.last_modified)
+                .ok()
+                .map(|d| d.with_timezone(&Utc)),
+            size: v.size,
+        });
+    }
+
+    Ok(result
+        .is_truncated
+        .then_some((result.next_key_marker, result.next_version_id_marker)))
+}
+
+type Marker = Option<(String, String)>;
+
+async fn list_s3(
+    api: &Api,
+    prefix: &str,
+    marker: Marker,
+    versions: &mut Vec<ObjectVersion>,
+) -> Result<Marker> {
+    let mut req = api
+        .bucket(Method::GET)
+        .query("versions", "")
+        .query("prefix", prefix);
+    if let Some((key, version_id)) = marker {
+        req = req.query("key-marker", key);
+        if !version_id.is_empty() {
+            req = req.query("version-id-marker", version_id);
+        }
+    }
+    let resp = req.send().await?;
+    parse_s3(&String::from_utf8_lossy(&resp.body), versions)
+}
+
+async fn list_gcs(
+    api: &Api,
+    prefix: &str,
+    marker: Marker,
+    versions: &mut Vec<ObjectVersion>,
+) -> Result<Marker> {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "camelCase", default)]
+    struct Objects {
+        items: Vec<Item>,
+        next_page_token: Option<String>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "camelCase", default)]
+    struct Item {
+        name: String,
+        generation: String,
+        size: String,
+        updated: String,
+        /// Set on the generations replaced or deleted.
+        time_deleted: Option<String>,
+    }
+
+    let path = format!("storage/v1/b/{}/o", api.bucket_name());
+    let mut req = api
+        .endpoint(Method::GET, &path)
+        .query("versions", "true")
+        .query("prefix", prefix);
+    if let Some((token, _)) = marker {
+        req = req.query("pageToken", token);
+    }
+    let result: Objects = req.send().await?.json()?;
+    let start = versions.len();
+    versions.extend(result.items.into_iter().map(|item| {
+        ObjectVersion {
+            key: item.name,
+            version_id: item.generation,
+            is_latest: item.time_deleted.is_none(),
+            is_delete_marker: false,
+            last_modified: DateTime::parse_from_rfc3339(&item.updated)
+                .ok()
+                .map(|d| d.with_timezone(&Utc)),
+            size: item.size.parse().unwrap_or_default(),
+        }
+    }));
+    // Generations are listed oldest first.
+    versions[start..].sort_by(|a, b| a.key.cmp(&b.key).then(b.version_id.cmp(&a.version_id)));
+
+    Ok(result.next_page_token.map(|token| (token, String::new())))
+}
+
+async fn list_azblob(
+    api: &Api,
+    prefix: &str,
+    marker: Marker,
+    versions: &mut Vec<ObjectVersion>,
+) -> Result<Marker> {
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct EnumerationResults {
+        blobs: Blobs,
+        next_marker: Option<String>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Blobs {
+        #[serde(rename = "Blob")]
+        blobs: Vec<Blob>,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Blob {
+        name: String,
+        version_id: String,
+        is_current_version: bool,
+        properties: Properties,
+    }
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Properties {
+        #[serde(rename = "Last-Modified")]
+        last_modified: String,
+        #[serde(rename = "Content-Length")]
+        content_length: u64,
+    }
+
+    let mut req = api
+        .bucket(Method::GET)
+        .query("restype", "container")
+        .query("comp", "list")
+        .query("include", "versions")
+        .query("prefix", prefix);
+    if let Some((token, _)) = marker {
+        req = req.query("marker", token);
+    }
+    let result: EnumerationResults = req.send().await?.xml()?;
+    let start = versions.len();
+    versions.extend(result.blobs.blobs.into_iter().map(|blob| {
+        ObjectVersion {
+            key: blob.name,
+            version_id: blob.version_id,
+            is_latest: blob.is_current_version,
+            is_delete_marker: false,
+            last_modified: DateTime::parse_from_rfc2822(&blob.properties.last_modified)
+                .ok()
+                .map(|d| d.with_timezone(&Utc)),
+            size: blob.properties.content_length,
+        }
+    }));
+    // Version ids are timestamps, listed oldest first.
+    versions[start..].sort_by(|a, b| a.key.cmp(&b.key).then(b.version_id.cmp(&a.version_id)));
+
+    Ok(result
+        .next_marker
+        .filter(|m| !m.is_empty())
+        .map(|m| (m, String::new())))
+}
+
+/// Content of `version_id` from byte `offset`.
+pub(crate) async fn get(
+    api: &Api,
+    key: &str,
+    version_id: &str,
+    offset: u64,
+) -> Result<reqwest::Response> {
+    let mut req = versioned(api, Method::GET, key, version_id);
+    if offset > 0 {
+        req = req.header("range", &format!("bytes={offset}-"))?;
+    }
+    req.execute().await
+}
+
+/// Make `version_id` the current version of `key`, by copying it onto the
+/// key. The versions in between are kept.
+pub(crate) async fn restore(api: &Api, key: &str, version_id: &str) -> Result<()> {
+    api.object(Method::PUT, key)
+        .copy_source_version(api, key, version_id)?
+        .send_copy()
+        .await
+}
+
+/// Delete `version_id` of `key` for good. Deleting a delete marker brings the
+/// previous version back.
+pub(crate) async fn delete(api: &Api, key: &str, version_id: &str) -> Result<()> {
+    versioned(api, Method::DELETE, key, version_id)
+        .send()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_interleaved_versions() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListVersionsResult>
+  <Name>bucket</Name>
+  <Prefix>a</Prefix>
+  <IsTruncated>true</IsTruncated>
+  <NextKeyMarker>b</NextKeyMarker>
+  <NextVersionIdMarker>v4</NextVersionIdMarker>
+  <DeleteMarker>
+    <Key>a</Key><VersionId>v3</VersionId><IsLatest>true</IsLatest>
+    <LastModified>2024-05-01T10:00:00.000Z</LastModified>
+  </DeleteMarker>
+  <Version>
+    <Key>a</Key><VersionId>v2</VersionId><IsLatest>false</IsLatest>
+    <LastModified>2024-04-01T10:00:00.000Z</LastModified><Size>12</Size>
+  </Version>
+  <Version>
+    <Key>b</Key><VersionId>v4</VersionId><IsLatest>true</IsLatest>
+    <LastModified>2024-03-01T10:00:00.000Z</LastModified><Size>3</Size>
+  </Version>
+</ListVersionsResult>"#;
+        let mut versions = vec![];
+        let marker = parse_s3(text, &mut versions).unwrap();
+
+        assert_eq!(marker, Some(("b".to_string(), "v4".to_string())));
+        let ids: Vec<_> = versions
+            .iter()
+            .map(|v| (v.version_id.as_str(), v.is_delete_marker, v.size))
+            .collect();
+        assert_eq!(ids, [("v3", true, 0), ("v2", false, 12), ("v4", false, 3)]);
+        assert!(versions[0].is_latest && versions[0].last_modified.is_some());
+    }
+}
@@ -172,6 +172,15 @@ impl CCUi {
     pub fn text_ellipsis(&self, name: &str, max_rows: usize) -> LayoutJob {
         text_ellipsis(&self.egui_ctx.style(), name, max_rows)
     }
+
+    pub fn text_highlight(
+        &self,
+        name: &str,
+        ranges: &[std::ops::Range<usize>],
+        max_rows: usize,
+    ) -> LayoutJob {
+        text_highlight(&self.egui_ctx.style(), name, ranges, max_rows)
+    }
 }
 
 pub fn text_ellipsis(style: &egui::Style, name: &str, max_rows: usize) -> LayoutJob {
@@ -189,3 +198,53 @@ pub fn text_ellipsis(style: &egui::Style, name: &str, max_rows: usize) -> Layout
 
     job
 }
+
+/// Like [`text_ellipsis`], with the byte `ranges` of `name` highlighted.
+pub fn text_highlight(
+    style: &egui::Style,
+    name: &str,
+    ranges: &[std::ops::Range<usize>],
+    max_rows: usize,
+) -> LayoutJob {
+    let mut job = text_ellipsis(style, name, max_rows);
+    if ranges.is_empty() {
+        return job;
+    }
+
+    let normal = job.sections[0].format.clone();
+    let highlight = egui::TextFormat {
+        color: style.visuals.strong_text_color(),
+        background: style.visuals.selection.bg_fill,
+        ..normal.clone()
+    };
+    job.sections.clear();
+    let mut offset = 0;
+    for range in ranges {
+        let (start, end) = (range.start.max(offset), range.end.min(name.len()));
+        if start >= end || !name.is_char_boundary(start) || !name.is_char_boundary(end) {
+            continue;
+        }
+        if offset < start {
+            job.sections.push(egui::text::LayoutSection {
+                leading_space: 0.0,
+                byte_range: offset..start,
+                format: normal.clone(),
+            });
+        }
+        job.sections.push(egui::text::LayoutSection {
+            leading_space: 0.0,
+            byte_range: start..end,
+            format: highlight.clone(),
+        });
+        offset = end;
+    }
+    if offset < name.len() {
+        job.sections.push(egui::text::LayoutSection {
+            leading_space: 0.0,
+            byte_range: offset..name.len(),
+            format: normal,
+        });
+    }
+
+    job
+}
@@ -38,6 +38,13 @@ pub fn auth_page(ctx: &egui::Context, state: &mut State) {
                         ui.label("Bucket:");
                         ui.text_edit_singleline(&mut state.session.bucket);
                         ui.end_row();
+                        ui.label("CDN URL:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut state.session.cdn_url)
+                                .hint_text("https://cdn.example.com (optional)"),
+                        )
+                        .on_hover_text("Custom domain or CDN serving the bucket, for public links");
+                        ui.end_row();
                         ui.label("Note:");
                         ui.text_edit_singleline(&mut state.session.note);
                     });
@@ -23,13 +23,15 @@ pub fn main_page(ctx: &egui::Context, state: &mut State, frame: &mut eframe::Fra
                     state.toasts.error(err);
                     state.err = None;
                 }
-                let list_len = state.list.len();
-                if state.list.is_empty() {
+                let list_len = state.view.len();
+                if state.view.is_empty() {
                     ui.centered_and_justified(|ui| ui.heading("Nothing Here."));
                     return;
                 }
                 let (num_cols, num_rows, row_height) = match state.setting.show_type {
-                    ShowType::List => (1, list_len, ui.text_style_height(&egui::TextStyle::Body)),
+                    ShowType::List | ShowType::Flat => {
+                        (1, list_len, ui.text_style_height(&egui::TextStyle::Body))
+                    }
                     ShowType::Thumb => {
                         let w = ui.available_size();
                         let num_cols = (w.x / THUMB_LIST_WIDTH) as usize;
@@ -80,7 +82,9 @@ pub fn main_page(ctx: &egui::Context, state: &mut State, frame: &mut eframe::Fra
                         ui.allocate_ui_at_rect(rect, |viewport_ui| {
                             viewport_ui.skip_ahead_auto_ids(min_row); // Make sure we get consistent IDs.
                             match state.setting.show_type {
-                                ShowType::List => list_ui(state, viewport_ui, min_row..max_row),
+                                ShowType::List | ShowType::Flat => {
+                                    list_ui(state, viewport_ui, min_row..max_row)
+                                }
                                 ShowType::Thumb => {
                                     thumb_ui(state, viewport_ui, min_row..max_row, num_cols)
                                 }
@@ -1,18 +1,43 @@
 use crate::global;
 use crate::widgets::toasts::{ToastKind, Toasts};
 use crate::widgets::{
+    bucket_panel_ui,
     confirm::{Confirm, ConfirmAction},
-    log_panel_ui, transfer_panel_ui, FileView,
+    content_types::ContentTypeEditor,
+    content_types_ui,
+    link_export::LinkExport,
+    link_export_ui, log_panel_ui,
+    meta_editor::MetaEditor,
+    meta_editor_ui,
+    restore_dialog::RestoreDialog,
+    restore_dialog_ui,
+    search_panel::SearchPanel,
+    search_panel_ui,
+    tag_editor::TagEditor,
+    tag_editor_ui, transfer_panel_ui,
+    upload_link::UploadLinkDialog,
+    upload_link_ui,
+    versions_panel::{VersionPreview, VersionsPanel},
+    versions_panel_ui, FileView,
 };
 use crate::{spawn_evs, spawn_transfer};
-use cc_core::{log::LogItem, store, tracing, MemoryHistory, Session, Setting};
+use cc_core::{log::LogItem, store, tracing, MemoryHistory, Session, Setting, ShowType};
 use cc_files::{Cache as ImageCache, FileType};
 
+use cc_storage::filter::{Filter, FilterMode};
+use cc_storage::link_export::SignedLinks;
+use cc_storage::meta::{MetaPatch, ObjectMeta};
+use cc_storage::restore::RestoreState;
+use cc_storage::search::CancelToken;
+use cc_storage::tagging::{TagFilter, TagPatch, Tags};
 use cc_storage::util::get_name_form_path;
+use cc_storage::versions::ObjectVersion;
 use cc_storage::{
-    Bucket, Client, ListObjects, ListObjectsV2Params, Metadata, Object, Params,
-    Result as ClientResult, TransferManager,
+    BatchItem, Bucket, Client, ListObjects, ListObjectsV2Params, Metadata, OSSError, Object,
+    Params, Result as ClientResult, TransferManager,
 };
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 use std::{path::PathBuf, vec};
 
 const MAX_BUFFER_SIZE: u64 = 2 * 1024 * 1024;
@@ -37,15 +62,41 @@ pub enum Route {
     Auth,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Size,
+    Date,
+}
+
 pub enum NavgatorType {
     Back,
     Forward,
     New(String),
 }
 
+/// Keys picked for a copy or move, with the client of the session they
+/// belong to, so they can be pasted into another session.
 pub enum FileAction {
-    Copy(String),
-    Move(String),
+    Copy(Client, Vec<String>),
+    Move(Client, Vec<String>),
+}
+
+impl FileAction {
+    pub fn client(&self) -> &Client {
+        match self {
+            FileAction::Copy(client, _) | FileAction::Move(client, _) => client,
+        }
+    }
+
+    pub fn keys(&self) -> &[String] {
+        match self {
+            FileAction::Copy(_, keys) | FileAction::Move(_, keys) => keys,
+        }
+    }
+
+    pub fn is_move(&self) -> bool {
+        matches!(self, FileAction::Move(..))
+    }
 }
 
 pub enum Update {
@@ -53,15 +104,36 @@ pub enum Update {
     List(ClientResult<ListObjects>),
     Navgator(NavgatorType),
     Deleted(ClientResult<bool>),
+    DeletedMulti(ClientResult<Vec<BatchItem>>),
+    ObjectMeta((String, ClientResult<ObjectMeta>)),
+    ObjectTags(Vec<(String, Tags)>),
+    EditTags(Vec<String>),
+    TagsSaved(ClientResult<Vec<BatchItem>>),
+    StorageClassSet(ClientResult<Vec<BatchItem>>),
+    Restore(Vec<String>),
+    RestoreStarted(ClientResult<Vec<BatchItem>>),
+    DownloadChecked((String, ClientResult<RestoreState>)),
+    Downloaded((String, ClientResult<()>)),
+    Uploaded((String, ClientResult<()>)),
+    Versions((String, ClientResult<(Vec<ObjectVersion>, bool)>)),
+    VersionData((String, ClientResult<Vec<u8>>)),
+    VersionRestored((String, ClientResult<()>)),
+    VersionsDeleted(Vec<BatchItem>),
+    EditMeta(Vec<String>),
+    MetaSaved(ClientResult<Vec<BatchItem>>),
+    SearchFound((usize, Vec<Object>, usize)),
+    SearchDone((usize, ClientResult<usize>)),
     CreateFolder(ClientResult<bool>),
     ViewObject(Object),
     CloseObject,
     HeadObject(ClientResult<(Metadata, Vec<u8>)>),
     GetObject(ClientResult<(String, Vec<u8>)>),
     BucketInfo(ClientResult<Bucket>),
-    Copied(ClientResult<(String, bool)>),
+    Copied(ClientResult<Vec<BatchItem>>),
     DownloadObject(String),
     SignatureUrl(ClientResult<String>),
+    UploadLink(ClientResult<String>),
+    LinksSigned(ClientResult<(SignedLinks, Vec<BatchItem>)>),
     Confirm((String, ConfirmAction)),
     Prompt((String, ConfirmAction)),
     Toast((String, ToastKind)),
@@ -80,6 +152,7 @@ pub struct State {
     pub file_cache: ImageCache,
     pub logs: Vec<LogItem>,
     pub is_show_result: bool,
+    pub is_show_bucket: bool,
     pub current_path: String,
     pub navigator: MemoryHistory,
     confirm: Confirm,
@@ -91,11 +164,36 @@ pub struct State {
     pub status: Status,
     pub toasts: Toasts,
     pub filter_str: String,
+    pub filter_mode: FilterMode,
+    /// Why `filter_str` is not a valid pattern.
+    pub filter_err: Option<String>,
+    filter: Option<Filter>,
+    /// `key` or `key=value` the listed objects must be tagged with.
+    pub tag_filter_str: String,
+    tag_filter: Option<TagFilter>,
+    /// Tags of the listed objects, `None` while loading, only fetched to
+    /// filter by tag.
+    tags: HashMap<String, Option<Tags>>,
+    /// Indexes of the items of `list` matching the filter, with the parts of
+    /// their names to highlight.
+    pub view: Vec<(usize, Vec<Range<usize>>)>,
+    /// Order of `view`, descending when set, listing order when `None`.
+    pub sort: Option<(SortKey, bool)>,
     pub selected_item: usize,
     pub ctx: egui::Context,
     pub bucket: Option<Bucket>,
     pub file_action: Option<FileAction>,
     pub transfer_manager: TransferManager,
+    pub search: SearchPanel,
+    pub meta_editor: MetaEditor,
+    pub tag_editor: TagEditor,
+    pub restore_dialog: RestoreDialog,
+    pub upload_link: UploadLinkDialog,
+    pub link_export: LinkExport,
+    pub versions: VersionsPanel,
+    pub content_type_editor: ContentTypeEditor,
+    /// Metadata of `current_object`, shown in its properties.
+    pub current_meta: Option<ObjectMeta>,
 }
 
 impl State {
@@ -125,6 +223,10 @@ impl State {
                 .access_key(&session.key_id)
                 .access_secret(&session.key_secret)
                 .bucket(&session.bucket)
+                .cname(&session.cdn_url)
+                .part_size(setting.part_size)
+                .concurrency(setting.concurrency)
+                .content_types(setting.content_types.clone())
                 .build()
             {
                 Ok(cli) => {
@@ -153,6 +255,7 @@ impl State {
             file_cache: images,
             logs: vec![],
             is_show_result: false,
+            is_show_bucket: false,
             current_path,
             navigator,
             confirm: Confirm::new(confirm_tx),
@@ -163,11 +266,28 @@ impl State {
             status,
             toasts: Toasts::new(),
             filter_str: String::new(),
+            filter_mode: FilterMode::default(),
+            filter_err: None,
+            filter: None,
+            tag_filter_str: String::new(),
+            tag_filter: None,
+            tags: HashMap::new(),
+            view: vec![],
+            sort: None,
             selected_item: 0,
             ctx: ctx.clone(),
             bucket,
             file_action: None,
             transfer_manager: TransferManager::new(),
+            search: SearchPanel::default(),
+            meta_editor: MetaEditor::default(),
+            tag_editor: TagEditor::default(),
+            restore_dialog: RestoreDialog::default(),
+            upload_link: UploadLinkDialog::default(),
+            link_export: LinkExport::default(),
+            versions: VersionsPanel::default(),
+            content_type_editor: ContentTypeEditor::default(),
+            current_meta: None,
         };
 
         // this.next_query = Some(this.build_query(None));
@@ -193,13 +313,13 @@ impl State {
                 }
                 Update::List(result) => match result {
                     Ok(str) => {
-                        // if let Some(token) = str.next_continuation_token() {
-                        //     self.next_query = Some(self.build_query(Some(token.clone())));
-                        // } else {
-                        //     self.next_query = None;
-                        // }
+                        // A refresh, or a new folder or view, started another
+                        // listing since this page was requested.
+                        if str.generation() != self.next_query.generation {
+                            tracing::debug!("Drop stale page of `{}`", str.prefix());
+                            continue;
+                        }
                         self.next_query.start_after = str.start_after().to_string();
-                        self.next_query.prefix = str.prefix().to_string();
                         self.next_query.is_truncated = str.is_truncated();
                         self.set_list(str);
                         self.loading_more = false;
@@ -226,9 +346,210 @@ impl State {
                             self.navigator.push(path);
                         }
                     }
+                    self.filter_str.clear();
+                    self.filter = None;
+                    self.filter_err = None;
+                    self.tag_filter_str.clear();
+                    self.tag_filter = None;
                     self.current_path = self.navigator.location();
                     self.refresh();
                 }
+                Update::SearchFound((generation, mut objects, scanned)) => {
+                    if generation == self.search.generation {
+                        self.search.results.append(&mut objects);
+                        self.search.scanned = scanned;
+                    }
+                }
+                Update::SearchDone((generation, result)) => {
+                    if generation != self.search.generation {
+                        continue;
+                    }
+                    self.search.running = None;
+                    if let Err(err) = result {
+                        self.search.err = Some(err.to_string());
+                    }
+                }
+                Update::DeletedMulti(result) => match result {
+                    Ok(items) => {
+                        let failed = items.iter().filter(|item| !item.is_ok()).count();
+                        for item in items {
+                            self.logs.push(match item.error {
+                                Some(err) => {
+                                    LogItem::delete().with_error(format!("{}: {}", item.key, err))
+                                }
+                                None => LogItem::delete().with_success(item.key),
+                            });
+                        }
+                        if failed > 0 {
+                            self.toasts
+                                .error(format!("Delete failed for {failed} items, see the logs."));
+                        }
+                        self.refresh();
+                    }
+                    Err(err) => {
+                        self.status = Status::Idle(Route::List);
+                        self.toasts.error("Delete failed.");
+                        self.logs
+                            .push(LogItem::delete().with_error(err.to_string()));
+                    }
+                },
+                Update::ObjectMeta((key, result)) => match result {
+                    Ok(meta) => {
+                        if self.meta_editor.loading && self.meta_editor.keys == [key.as_str()] {
+                            self.meta_editor.set_meta(&meta);
+                        }
+                        if key == self.current_object.key() {
+                            if meta.restore.is_readable()
+                                && self
+                                    .client()
+                                    .is_archive_class(self.current_object.storage_class())
+                                && self.current_meta.is_none()
+                            {
+                                self.head_object(&key);
+                            }
+                            self.current_meta = Some(meta);
+                        }
+                    }
+                    Err(err) => {
+                        if self.meta_editor.loading {
+                            self.meta_editor.is_show = false;
+                            self.toasts.error("Get metadata failed.");
+                        }
+                        self.logs
+                            .push(LogItem::meta().with_error(format!("{key}: {err}")));
+                    }
+                },
+                Update::ObjectTags(items) => {
+                    for (key, tags) in items {
+                        if self.tag_editor.loading && self.tag_editor.keys == [key.as_str()] {
+                            self.tag_editor.set_tags(&tags);
+                        }
+                        self.tags.insert(key, Some(tags));
+                    }
+                    self.apply_filter();
+                }
+                Update::EditTags(keys) => {
+                    self.tag_editor.open(keys);
+                    if self.tag_editor.loading {
+                        self.get_tags(self.tag_editor.keys.clone());
+                    }
+                }
+                Update::TagsSaved(result) => {
+                    self.batch_saved(LogItem::tag, "Tags update", "Tags updated.", result)
+                }
+                Update::EditMeta(keys) => {
+                    self.meta_editor.open(keys);
+                    if self.meta_editor.loading {
+                        self.get_object_meta(self.meta_editor.keys[0].clone());
+                    }
+                }
+                Update::StorageClassSet(result) => self.batch_saved(
+                    LogItem::storage_class,
+                    "Storage class change",
+                    "Storage class changed.",
+                    result,
+                ),
+                Update::MetaSaved(result) => self.batch_saved(
+                    LogItem::meta,
+                    "Metadata update",
+                    "Metadata updated.",
+                    result,
+                ),
+                Update::Restore(keys) => {
+                    // Only the tiers every selected object takes.
+                    let client = self.client();
+                    let mut tiers = client.restore_tiers().to_vec();
+                    for obj in self
+                        .list
+                        .iter()
+                        .filter(|o| keys.iter().any(|k| k == o.key()))
+                    {
+                        let allowed = client.class_restore_tiers(obj.storage_class());
+                        tiers.retain(|tier| allowed.contains(tier));
+                    }
+                    self.restore_dialog.open(keys, tiers);
+                }
+                Update::RestoreStarted(result) => {
+                    self.batch_saved(LogItem::restore, "Restore", "Restore started.", result)
+                }
+                Update::Versions((key, result)) => {
+                    if key == self.versions.key {
+                        match result {
+                            Ok((versions, truncated)) => {
+                                self.versions.set_versions(versions, truncated)
+                            }
+                            Err(err) => {
+                                self.versions.loading = false;
+                                self.versions.err = Some(err.to_string());
+                            }
+                        }
+                    }
+                }
+                Update::VersionData((uri, result)) => match result {
+                    Ok(data) => {
+                        self.file_cache.add(&uri, data);
+                        self.set_version_image(&uri);
+                    }
+                    Err(err) => {
+                        if self.versions.preview.as_ref().is_some_and(|p| p.uri == uri) {
+                            self.versions.preview = None;
+                        }
+                        self.toasts.error("Get version failed.");
+                        self.logs
+                            .push(LogItem::unknow().with_error(format!("{uri}: {err}")));
+                    }
+                },
+                Update::VersionRestored((key, result)) => match result {
+                    Ok(_) => {
+                        self.toasts.success("Version restored.");
+                        self.logs.push(LogItem::version().with_success(key));
+                        self.list_versions(self.versions.key.clone());
+                        self.refresh();
+                    }
+                    Err(err) => {
+                        self.toasts.error("Restore version failed.");
+                        self.logs
+                            .push(LogItem::version().with_error(format!("{key}: {err}")));
+                    }
+                },
+                Update::VersionsDeleted(items) => {
+                    self.batch_saved(
+                        LogItem::version,
+                        "Version delete",
+                        "Versions deleted.",
+                        Ok(items),
+                    );
+                    self.list_versions(self.versions.key.clone());
+                }
+                Update::DownloadChecked((key, result)) => match result {
+                    Ok(state) if !state.is_readable() => {
+                        self.toasts.error(format!("{key}: {}", state.describe()));
+                    }
+                    Ok(_) => self.save_file(key),
+                    Err(err) => {
+                        self.toasts.error("Download failed.");
+                        self.logs
+                            .push(LogItem::download().with_error(format!("{key}: {err}")));
+                    }
+                },
+                Update::Downloaded((key, result)) => match result {
+                    Ok(_) => self.logs.push(LogItem::download().with_success(key)),
+                    Err(err) => {
+                        self.toasts.error("Download failed.");
+                        self.logs
+                            .push(LogItem::download().with_error(format!("{key}: {err:#}")));
+                    }
+                },
+                Update::Uploaded((dest, result)) => match result {
+                    Ok(_) => self
+                        .logs
+                        .push(LogItem::upload().with_success(format!("Uploaded to /{dest}"))),
+                    Err(err) => {
+                        self.toasts.error("Upload failed.");
+                        self.logs
+                            .push(LogItem::upload().with_error(format!("{err:#}")));
+                    }
+                },
                 Update::Deleted(result) => match result {
                     Ok(success) => {
                         if success {
@@ -258,13 +579,25 @@ impl State {
                     }
                 },
                 Update::ViewObject(obj) => {
-                    self.head_object(obj.key());
-                    self.current_object = obj;
+                    // Archived content is only read once the metadata says
+                    // it was restored.
+                    if !self.client().is_archive_class(obj.storage_class()) {
+                        self.head_object(obj.key());
+                    }
+                    self.get_object_meta(obj.key().to_string());
+                    self.current_meta = None;
                     self.file_view.reset();
+                    self.file_view.set_links(
+                        self.client().public_url(obj.key()),
+                        self.client().cdn_url(obj.key()),
+                        self.bucket_is_private(),
+                    );
                     self.file_view.show();
+                    self.current_object = obj;
                 }
                 Update::CloseObject => {
                     self.current_object = Default::default();
+                    self.current_meta = None;
                 }
                 Update::GetObject(result) => match result {
                     Ok((_name, data)) => {
@@ -295,8 +628,16 @@ impl State {
                         }
                         if headers.content_length() <= MAX_BUFFER_SIZE {
                             if self.current_object.mine_type().starts_with("image/") {
-                                // do nothing
-                                self.get_signature_url(self.current_object.key().to_string(), 3600);
+                                if self.bucket_is_private() {
+                                    self.get_signature_url(
+                                        self.current_object.key().to_string(),
+                                        3600,
+                                        None,
+                                    );
+                                } else {
+                                    let url = self.client().public_url(self.current_object.key());
+                                    self.current_object.set_url(url);
+                                }
                                 self.file_cache.add(self.current_object.key(), data_clone);
                             } else {
                                 self.get_current_object();
@@ -320,17 +661,42 @@ impl State {
                     }
                 },
                 Update::Copied(result) => match result {
-                    Ok((file, is_move)) => {
+                    Ok(items) => {
                         self.file_action = None;
-                        if is_move {
-                            self.delete_object(file);
+                        let failed = items.iter().filter(|item| !item.is_ok()).count();
+                        for item in items {
+                            self.logs.push(match item.error {
+                                Some(err) => {
+                                    LogItem::copy().with_error(format!("{}: {}", item.key, err))
+                                }
+                                None => LogItem::copy().with_success(item.key),
+                            });
+                        }
+                        if failed > 0 {
+                            self.toasts
+                                .error(format!("Copy failed for {failed} items, see the logs."));
                         }
                         self.refresh();
                     }
                     Err(err) => {
                         self.status = Status::Idle(Route::List);
-                        self.toasts.error("Copy failed.");
-                        self.logs.push(LogItem::copy().with_error(err.to_string()));
+                        match err.downcast_ref::<OSSError>() {
+                            Some(OSSError::Conflict(keys)) if self.file_action.is_some() => {
+                                self.confirm.show(
+                                    format!(
+                                        "{} already exist in {}, do you confirm to overwrite them?\n{}",
+                                        keys.len(),
+                                        self.current_path,
+                                        keys.join("\n")
+                                    ),
+                                    ConfirmAction::OverwritePaste(self.current_path.clone()),
+                                );
+                            }
+                            _ => {
+                                self.toasts.error("Copy failed.");
+                                self.logs.push(LogItem::copy().with_error(err.to_string()));
+                            }
+                        }
                     }
                 },
                 Update::DownloadObject(name) => {
@@ -339,12 +705,36 @@ impl State {
                 Update::SignatureUrl(result) => match result {
                     Ok(url) => {
                         self.current_object.set_url(url.clone());
+                        self.file_view.set_signed_url(url);
                     }
                     Err(err) => {
                         self.toasts.error("Signature Url failed.");
                         self.logs.push(LogItem::copy().with_error(err.to_string()));
                     }
                 },
+                Update::UploadLink(result) => match result {
+                    Ok(url) => self.upload_link.set_url(url),
+                    Err(err) => {
+                        self.upload_link.loading = false;
+                        self.upload_link.err = Some(err.to_string());
+                    }
+                },
+                Update::LinksSigned(result) => match result {
+                    Ok((links, items)) => {
+                        let failed: Vec<_> = items.into_iter().filter(|i| !i.is_ok()).collect();
+                        for item in &failed {
+                            let err = item.error.as_deref().unwrap_or_default();
+                            self.logs
+                                .push(LogItem::link().with_error(format!("{}: {}", item.key, err)));
+                        }
+                        self.link_export.set_links(links, failed.len());
+                    }
+                    Err(err) => {
+                        self.link_export.loading = false;
+                        self.toasts.error("Export links failed.");
+                        self.logs.push(LogItem::link().with_error(err.to_string()));
+                    }
+                },
                 Update::Prompt((message, action)) => self.confirm.prompt(message, action),
                 Update::Confirm((message, action)) => self.confirm.show(message, action),
                 Update::Toast((message, t)) => self.toasts.msg(message, t),
@@ -373,9 +763,24 @@ impl State {
         if self.client.is_some() {
             if !self.current_object.key().is_empty() && self.current_object.is_file() {
                 let file = self.file_cache.check(self.current_object.key());
-                self.file_view.ui(ctx, &self.current_object, file);
+                self.file_view.ui(
+                    ctx,
+                    &self.current_object,
+                    self.current_meta.as_ref(),
+                    file,
+                    &self.session.service,
+                );
             }
             log_panel_ui(ctx, self);
+            bucket_panel_ui(ctx, self);
+            meta_editor_ui(ctx, self);
+            tag_editor_ui(ctx, self);
+            restore_dialog_ui(ctx, self);
+            upload_link_ui(ctx, self);
+            link_export_ui(ctx, self);
+            versions_panel_ui(ctx, self);
+            content_types_ui(ctx, self);
+            search_panel_ui(ctx, self);
             transfer_panel_ui(ctx, self);
         }
 
@@ -394,13 +799,34 @@ impl State {
     //     self.bucket.as_ref().expect("Bucket not initialized yet")
     // }
 
-    // pub fn bucket_is_private(&self) -> bool {
-    //     self.bucket().is_private()
-    // }
+    /// Private until the bucket info says otherwise.
+    pub fn bucket_is_private(&self) -> bool {
+        self.bucket.as_ref().is_none_or(|b| b.is_private())
+    }
+
+    pub fn export_links(&mut self, keys: Vec<String>, expire: u64) {
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.signature_urls(keys, expire, transfer).await;
+            evs.send(Update::LinksSigned(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn presign_upload(&self, key: String, expire: u64, content_type: String) {
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client
+                .presign_upload(&key, expire, Some(content_type.as_str()))
+                .await;
+            evs.send(Update::UploadLink(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
 
-    pub fn get_signature_url(&self, name: String, expire: u64) {
+    pub fn get_signature_url(&self, name: String, expire: u64, params: Option<Params>) {
         spawn_evs!(self, |evs, client, ctx| {
-            let res = client.signature_url(&name, expire, None).await;
+            let res = client.signature_url(&name, expire, params).await;
             evs.send(Update::SignatureUrl(res)).unwrap();
             ctx.request_repaint();
         });
@@ -417,13 +843,18 @@ impl State {
         self.transfer_manager.show("upload");
 
         spawn_transfer!(self, |transfer, evs, client, ctx| {
-            let _ = client.put_multi(picked_path, dest, transfer).await;
+            let res = client.put_multi(picked_path, dest.clone(), transfer).await;
+            evs.send(Update::Uploaded((dest, res))).unwrap();
             evs.send(Update::TransferResult).unwrap();
             ctx.request_repaint();
         });
     }
 
     pub fn delete_object(&mut self, file: String) {
+        if file.ends_with('/') {
+            self.delete_keys(vec![file]);
+            return;
+        }
         self.status = Status::Busy(Route::List);
 
         spawn_evs!(self, |evs, client, ctx| {
@@ -433,18 +864,164 @@ impl State {
         });
     }
 
+    /// Keys of the selected items, in listing order.
+    pub fn selected_keys(&self) -> Vec<String> {
+        self.list
+            .iter()
+            .filter(|x| x.selected)
+            .map(|x| x.key().to_string())
+            .collect()
+    }
+
     pub fn delete_multi_object(&mut self) {
+        let keys = self.selected_keys();
+        self.delete_keys(keys);
+    }
+
+    fn delete_keys(&mut self, keys: Vec<String>) {
         self.status = Status::Busy(Route::List);
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.delete_multi_object(keys, transfer).await;
+            evs.send(Update::DeletedMulti(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
 
-        let files: Vec<Object> = self.list.iter().filter(|x| x.selected).cloned().collect();
+    pub fn set_content_types(&mut self, content_types: BTreeMap<String, String>) {
+        if let Some(client) = &self.client {
+            client.set_content_types(content_types.clone());
+        }
+        self.setting.content_types = content_types;
+        self.setting.store();
+    }
 
+    pub fn get_object_meta(&self, key: String) {
         spawn_evs!(self, |evs, client, ctx| {
-            let res = client.delete_multi_object(files).await;
-            evs.send(Update::Deleted(res)).unwrap();
+            let res = client.object_meta(&key).await;
+            evs.send(Update::ObjectMeta((key, res))).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn save_meta(&mut self, keys: Vec<String>, patch: MetaPatch) {
+        self.status = Status::Busy(Route::List);
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.patch_object_meta(keys, patch, transfer).await;
+            evs.send(Update::MetaSaved(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    /// Log the result of an operation on each object of a selection, such as
+    /// a metadata update, then reload what it changed. `log` makes the log
+    /// items of the operation.
+    fn batch_saved(
+        &mut self,
+        log: fn() -> LogItem,
+        what: &str,
+        done: &str,
+        result: ClientResult<Vec<BatchItem>>,
+    ) {
+        match result {
+            Ok(items) => {
+                let failed = items.iter().filter(|item| !item.is_ok()).count();
+                for item in items {
+                    self.logs.push(match item.error {
+                        Some(err) => log().with_error(format!("{}: {}", item.key, err)),
+                        None => log().with_success(item.key),
+                    });
+                }
+                if failed > 0 {
+                    self.toasts
+                        .error(format!("{what} failed for {failed} items, see the logs."));
+                } else {
+                    self.toasts.success(done);
+                }
+                if !self.current_object.key().is_empty() {
+                    self.get_object_meta(self.current_object.key().to_string());
+                }
+                self.refresh();
+            }
+            Err(err) => {
+                self.status = Status::Idle(Route::List);
+                self.toasts.error(format!("{what} failed."));
+                self.logs.push(log().with_error(err.to_string()));
+            }
+        }
+    }
+
+    pub fn restore_objects(&mut self, keys: Vec<String>, days: u32, tier: String) {
+        self.status = Status::Busy(Route::List);
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.restore_objects(keys, days, tier, transfer).await;
+            evs.send(Update::RestoreStarted(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn set_storage_class(&mut self, keys: Vec<String>, class: String) {
+        self.status = Status::Busy(Route::List);
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.set_storage_class(keys, class, transfer).await;
+            evs.send(Update::StorageClassSet(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    fn get_tags(&self, keys: Vec<String>) {
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client.objects_tags(keys).await;
+            evs.send(Update::ObjectTags(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn save_tags(&mut self, keys: Vec<String>, patch: TagPatch) {
+        self.status = Status::Busy(Route::List);
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.tag_multi_object(keys, patch, transfer).await;
+            evs.send(Update::TagsSaved(res)).unwrap();
             ctx.request_repaint();
         });
     }
 
+    /// Show only the objects tagged as `tag_filter_str`, and the folders.
+    /// Tags are fetched as objects are listed.
+    pub fn filter_by_tag(&mut self) {
+        self.tag_filter = TagFilter::parse(&self.tag_filter_str);
+        self.fetch_tags();
+        self.apply_filter();
+    }
+
+    fn fetch_tags(&mut self) {
+        if self.tag_filter.is_none() {
+            return;
+        }
+        let keys: Vec<String> = self
+            .list
+            .iter()
+            .filter(|obj| obj.is_file() && !self.tags.contains_key(obj.key()))
+            .map(|obj| obj.key().to_string())
+            .collect();
+        if keys.is_empty() {
+            return;
+        }
+        for key in &keys {
+            self.tags.insert(key.clone(), None);
+        }
+        self.get_tags(keys);
+    }
+
     pub fn create_folder(&mut self, name: String) {
         self.status = Status::Busy(Route::List);
 
@@ -472,9 +1049,32 @@ impl State {
 
     pub fn copy_object(&mut self, src: String, dest: String, is_move: bool) {
         self.status = Status::Busy(Route::List);
+        if src.ends_with('/') {
+            self.transfer_manager.show("task");
+        }
 
-        spawn_evs!(self, |evs, client, ctx| {
-            let res = client.copy_object(src, dest, is_move).await;
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client.copy_object(src, dest, is_move, transfer).await;
+            evs.send(Update::Copied(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    /// Copy or move the keys of the current [`FileAction`] into `dest`.
+    pub fn paste(&mut self, dest: String, overwrite: bool) {
+        let Some(action) = &self.file_action else {
+            return;
+        };
+        let source = action.client().clone();
+        let keys = action.keys().to_vec();
+        let is_move = action.is_move();
+        self.status = Status::Busy(Route::List);
+        self.transfer_manager.show("task");
+
+        spawn_transfer!(self, |transfer, evs, client, ctx| {
+            let res = client
+                .copy_multi_object(&source, keys, &dest, is_move, overwrite, transfer)
+                .await;
             evs.send(Update::Copied(res)).unwrap();
             ctx.request_repaint();
         });
@@ -503,6 +1103,10 @@ impl State {
             self.status = Status::Busy(Route::List);
         }
 
+        self.next_query.prefix = self.list_prefix();
+        // The flat view lists the tree, a page at a time like a folder.
+        self.next_query.recursive = self.setting.show_type == ShowType::Flat;
+        self.next_query.max_keys = self.setting.page_limit as usize;
         let query = self.next_query.clone();
 
         spawn_evs!(self, |evs, client, ctx| {
@@ -521,6 +1125,8 @@ impl State {
             .collect();
         self.list.append(&mut dirs);
         self.list.append(&mut files);
+        self.fetch_tags();
+        self.apply_filter();
     }
 
     pub fn load_more(&mut self) {
@@ -537,32 +1143,122 @@ impl State {
         self.scroll_top = true;
         let current_path = self.navigator.location();
         self.current_path = current_path;
-        self.next_query = ListObjectsV2Params::default();
+        self.next_query = ListObjectsV2Params {
+            generation: self.next_query.generation + 1,
+            ..Default::default()
+        };
         self.list = vec![];
+        self.view = vec![];
+        self.tags.clear();
         self.get_list();
     }
 
+    /// Apply `filter_str` with `filter_mode`, prefixes are listed by the
+    /// server and the other modes match the loaded items.
     pub fn filter(&mut self) {
-        self.refresh();
+        match Filter::new(self.filter_mode, &self.filter_str) {
+            Ok(filter) => {
+                self.filter = Some(filter);
+                self.filter_err = None;
+            }
+            Err(err) => {
+                self.filter = None;
+                self.filter_err = Some(format!("{err:#}"));
+            }
+        }
+        if self.list_prefix() != self.next_query.prefix {
+            self.refresh();
+        } else {
+            self.apply_filter();
+        }
     }
 
-    fn _build_query(&self, next_token: Option<String>) -> Params {
+    fn apply_filter(&mut self) {
+        let filter = self.filter.as_ref().filter(|f| !f.is_server_side());
+        self.view = vec![];
+        for (i, obj) in self.list.iter_mut().enumerate() {
+            if let Some(tag_filter) = self.tag_filter.as_ref().filter(|_| obj.is_file()) {
+                // Rows stay visible until their tags arrive.
+                let tagged = match self.tags.get(obj.key()) {
+                    Some(Some(tags)) => tag_filter.matches(tags),
+                    _ => true,
+                };
+                if !tagged {
+                    obj.selected = false;
+                    continue;
+                }
+            }
+            let found = match filter {
+                Some(filter) => filter.find(&obj.name()),
+                None => match &self.filter {
+                    // Highlight the prefix listed by the server.
+                    Some(filter) => Some(filter.find(&obj.name()).unwrap_or_default()),
+                    None => Some(vec![]),
+                },
+            };
+            match found {
+                Some(ranges) => self.view.push((i, ranges)),
+                // Hidden items can not stay selected.
+                None => obj.selected = false,
+            }
+        }
+
+        if let Some((key, descending)) = self.sort {
+            let list = &self.list;
+            self.view.sort_by(|(a, _), (b, _)| {
+                let (a, b) = (&list[*a], &list[*b]);
+                let order = match key {
+                    SortKey::Size => a.size().cmp(&b.size()),
+                    SortKey::Date => a.last_modified().cmp(&b.last_modified()),
+                };
+                if descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+        }
+    }
+
+    /// Sort by `key` descending, then ascending, then back to listing order.
+    pub fn toggle_sort(&mut self, key: SortKey) {
+        self.sort = match self.sort {
+            Some((k, true)) if k == key => Some((key, false)),
+            Some((k, false)) if k == key => None,
+            _ => Some((key, true)),
+        };
+        self.apply_filter();
+    }
+
+    /// Name shown for `obj`, the path relative to the current folder in the
+    /// flat view.
+    pub fn display_name<'a>(&self, obj: &'a Object) -> std::borrow::Cow<'a, str> {
+        if self.setting.show_type == ShowType::Flat {
+            let prefix = self.current_path.trim_end_matches('/');
+            let key = obj.key();
+            let relative = if prefix.is_empty() {
+                key
+            } else {
+                key.strip_prefix(prefix)
+                    .and_then(|k| k.strip_prefix('/'))
+                    .unwrap_or(key)
+            };
+            std::borrow::Cow::Borrowed(relative)
+        } else {
+            std::borrow::Cow::Owned(obj.name())
+        }
+    }
+
+    /// Prefix listed for the current path and a prefix filter.
+    fn list_prefix(&self) -> String {
         let mut path = self.current_path.clone();
         if !path.ends_with('/') && !path.is_empty() {
             path.push('/');
         }
-        if !self.filter_str.is_empty() {
-            path.push_str(&self.filter_str);
-        }
-        let mut query = Params::new();
-        query.insert("list-type".into(), Some("2".to_string()));
-        query.insert("prefix".into(), Some(path));
-        query.insert("delimiter".into(), Some("/".into()));
-        query.insert("max-keys".into(), Some(self.setting.page_limit.to_string()));
-        if let Some(token) = next_token {
-            query.insert("continuation-token".into(), Some(token));
+        if let Some(filter) = self.filter.as_ref().filter(|f| f.is_server_side()) {
+            path.push_str(filter.pattern());
         }
-        query
+        path
     }
 
     pub fn login(&mut self) -> ClientResult<()> {
@@ -572,6 +1268,10 @@ impl State {
             .access_key(&self.session.key_id)
             .access_secret(&self.session.key_secret)
             .bucket(&self.session.bucket)
+            .cname(&self.session.cdn_url)
+            .part_size(self.setting.part_size)
+            .concurrency(self.setting.concurrency)
+            .content_types(self.setting.content_types.clone())
             .build()?;
 
         store::put_session(&self.session)?;
@@ -610,18 +1310,72 @@ impl State {
                 ConfirmAction::RemoveFiles => {
                     self.delete_multi_object();
                 }
+                ConfirmAction::RestoreVersion((key, version_id)) => {
+                    self.restore_version(key, version_id);
+                }
+                ConfirmAction::DeleteVersions(items) => {
+                    self.delete_versions(items);
+                }
+                ConfirmAction::SetStorageClass((keys, class)) => {
+                    self.set_storage_class(keys, class);
+                }
                 ConfirmAction::GenerateUrl(expire) => {
-                    let name = self.current_object.key();
-                    self.get_signature_url(name.to_string(), expire);
+                    let name = self.current_object.key().to_string();
+                    let params = self.file_view.link_params.to_params(&name);
+                    self.get_signature_url(name, expire, params);
+                }
+                ConfirmAction::OverwritePaste(dest) => {
+                    self.paste(dest, true);
                 }
                 ConfirmAction::RenameObject((src, name)) => {
-                    let dest = format!("{}{}", self.current_path, name);
+                    let mut dest = format!("{}{}", self.current_path, name.trim_end_matches('/'));
+                    if src.ends_with('/') {
+                        dest.push('/');
+                    }
                     self.copy_object(src, dest, true);
                 }
             }
         }
     }
 
+    /// Search the bucket with the criteria of the search panel, results are
+    /// added to it as they are found.
+    pub fn start_search(&mut self) {
+        self.stop_search();
+        self.search.results = vec![];
+        self.search.scanned = 0;
+        self.search.err = None;
+        let query = match self.search.query(&self.current_path) {
+            Ok(query) => query,
+            Err(err) => {
+                self.search.err = Some(format!("{err:#}"));
+                return;
+            }
+        };
+        let cancel = CancelToken::default();
+        self.search.running = Some(cancel.clone());
+        self.search.generation += 1;
+        let generation = self.search.generation;
+
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client
+                .search(query, cancel, |objects, scanned| {
+                    evs.send(Update::SearchFound((generation, objects, scanned)))
+                        .unwrap();
+                    ctx.request_repaint();
+                })
+                .await;
+            evs.send(Update::SearchDone((generation, res))).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn stop_search(&mut self) {
+        if let Some(cancel) = self.search.running.take() {
+            cancel.cancel();
+        }
+    }
+
     pub fn load_all_session(&mut self) -> Vec<Session> {
         let mut sessions = vec![];
         match store::get_all_session() {
@@ -635,11 +1389,131 @@ impl State {
     }
 
     pub fn download_file(&mut self, name: String) {
+        if name.ends_with('/') {
+            self.download_folder(name);
+            return;
+        }
+        let archived = self
+            .list
+            .iter()
+            .find(|obj| obj.key() == name)
+            .is_some_and(|obj| self.client().is_archive_class(obj.storage_class()));
+        if archived {
+            spawn_evs!(self, |evs, client, ctx| {
+                let res = client.restore_status(&name).await;
+                evs.send(Update::DownloadChecked((name, res))).unwrap();
+                ctx.request_repaint();
+            });
+            return;
+        }
+        self.save_file(name);
+    }
+
+    pub fn show_versions(&mut self, key: String) {
+        self.versions.open(key.clone());
+        self.list_versions(key);
+    }
+
+    pub fn list_versions(&mut self, key: String) {
+        self.versions.loading = true;
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client.list_versions(&key).await;
+            evs.send(Update::Versions((key, res))).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn preview_version(&mut self, version: ObjectVersion) {
+        if version.size > MAX_BUFFER_SIZE {
+            self.toasts
+                .info("This version is too large to preview, download it instead.");
+            return;
+        }
+        let uri = format!("bytes://{}@{}", version.key, version.version_id);
+        self.versions.preview = Some(VersionPreview {
+            title: format!(
+                "{} ({})",
+                get_name_form_path(&version.key),
+                version.date_string()
+            ),
+            uri: uri.clone(),
+            image: None,
+        });
+        if self.file_cache.check(&uri).is_some() {
+            self.set_version_image(&uri);
+            return;
+        }
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client.get_version(&version.key, &version.version_id).await;
+            evs.send(Update::VersionData((uri, res))).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    /// Images of the previewed version are shown from memory.
+    fn set_version_image(&mut self, uri: &str) {
+        if let (Some(preview), Some(FileType::StaticImage(data))) =
+            (&mut self.versions.preview, self.file_cache.check(uri))
+        {
+            if preview.uri == uri {
+                preview.image = Some(data.clone().into());
+            }
+        }
+    }
+
+    pub fn download_version(&mut self, version: ObjectVersion) {
+        let file_name = get_name_form_path(&version.key);
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            self.transfer_manager.show("download");
+            spawn_transfer!(self, |transfer, evs, client, ctx| {
+                let res = client.download_version(&version, path, transfer).await;
+                evs.send(Update::Downloaded((version.key, res))).unwrap();
+                evs.send(Update::TransferResult).unwrap();
+                ctx.request_repaint();
+            });
+        }
+    }
+
+    fn restore_version(&mut self, key: String, version_id: String) {
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client.restore_version(&key, &version_id).await;
+            evs.send(Update::VersionRestored((key, res))).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    fn delete_versions(&mut self, items: Vec<(String, String)>) {
+        spawn_evs!(self, |evs, client, ctx| {
+            let res = client.delete_versions(items).await;
+            evs.send(Update::VersionsDeleted(res)).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    /// Ask where to save `name` and download it.
+    fn save_file(&mut self, name: String) {
         let file_name = get_name_form_path(&name);
         self.transfer_manager.show("download");
         if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
             spawn_transfer!(self, |transfer, evs, client, ctx| {
-                let _ = client.download_file(&name, path, transfer).await;
+                let res = client.download_file(&name, path, transfer).await;
+                evs.send(Update::Downloaded((name, res))).unwrap();
+                evs.send(Update::TransferResult).unwrap();
+                ctx.request_repaint();
+            });
+        }
+    }
+
+    /// Download a whole prefix into a folder named after it, inside the
+    /// chosen local directory.
+    pub fn download_folder(&mut self, prefix: String) {
+        let folder_name = get_name_form_path(&prefix);
+        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+            self.transfer_manager.show("download");
+            let target = dir.join(folder_name);
+            spawn_transfer!(self, |transfer, evs, client, ctx| {
+                let res = client.download_dir(&prefix, target, transfer).await;
+                evs.send(Update::Downloaded((prefix, res))).unwrap();
                 evs.send(Update::TransferResult).unwrap();
                 ctx.request_repaint();
             });
@@ -0,0 +1,75 @@
+use crate::state::State;
+use cc_ui::icon;
+
+pub fn bucket_panel_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.is_show_bucket;
+    let mut refresh = false;
+    egui::Window::new("Bucket")
+        .open(&mut is_show)
+        .default_width(320.0)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let Some(bucket) = &state.bucket else {
+                ui.spinner();
+                return;
+            };
+            let unknown = || "-".to_string();
+            egui::Grid::new("bucket_info")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.label(bucket.name());
+                    ui.end_row();
+
+                    ui.label("Service:");
+                    ui.label(state.session.service.to_string());
+                    ui.end_row();
+
+                    ui.label("Access:");
+                    let access = egui::RichText::new(bucket.grant().name());
+                    if bucket.is_private() {
+                        ui.label(access);
+                    } else {
+                        ui.label(access.color(ui.visuals().warn_fg_color))
+                            .on_hover_text("Objects are readable without signature");
+                    }
+                    ui.end_row();
+
+                    ui.label("Region:");
+                    ui.label(bucket.region.clone().unwrap_or_else(unknown));
+                    ui.end_row();
+
+                    ui.label("Created:");
+                    ui.label(
+                        bucket
+                            .creation_date
+                            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+                            .unwrap_or_else(unknown),
+                    );
+                    ui.end_row();
+
+                    ui.label("Versioning:");
+                    ui.label(bucket.versioning.name());
+                    ui.end_row();
+                });
+
+            if !bucket.warnings.is_empty() {
+                ui.separator();
+                for warning in &bucket.warnings {
+                    ui.colored_label(ui.visuals().warn_fg_color, warning);
+                }
+            }
+            ui.separator();
+            if ui.button(format!("{} Refresh", icon::REFRESH)).clicked() {
+                refresh = true;
+            }
+        });
+
+    if refresh {
+        state.bucket = None;
+        state.get_bucket_info();
+    }
+    state.is_show_bucket = is_show;
+}
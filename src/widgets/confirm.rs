@@ -8,8 +8,12 @@ pub enum ConfirmAction {
     RemoveFile(Object),
     CreateFolder(String),
     RemoveFiles,
+    SetStorageClass((Vec<String>, String)),
+    RestoreVersion((String, String)),
+    DeleteVersions(Vec<(String, String)>),
     GenerateUrl(u64),
     RenameObject((String, String)),
+    OverwritePaste(String),
 }
 
 #[derive(Clone, PartialEq, Debug)]
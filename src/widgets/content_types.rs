@@ -0,0 +1,87 @@
+use crate::state::State;
+use cc_ui::icon;
+
+/// Editor of the extension to Content-Type overrides of `Setting`.
+#[derive(Default)]
+pub struct ContentTypeEditor {
+    pub is_show: bool,
+    rows: Vec<(String, String)>,
+}
+
+impl ContentTypeEditor {
+    pub fn toggle(&mut self, rows: impl Iterator<Item = (String, String)>) {
+        self.is_show = !self.is_show;
+        self.rows = rows.collect();
+    }
+}
+
+pub fn content_types_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.content_type_editor.is_show;
+    let mut save = false;
+    egui::Window::new("Content-Type")
+        .open(&mut is_show)
+        .default_width(360.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label("Uploads use these types before the built-in detection.");
+            let rows = &mut state.content_type_editor.rows;
+            let mut remove = None;
+            egui::ScrollArea::vertical()
+                .id_source("content_types_scroller")
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("content_types_grid")
+                        .num_columns(3)
+                        .spacing([10.0, 6.0])
+                        .show(ui, |ui| {
+                            for (i, (ext, mime)) in rows.iter_mut().enumerate() {
+                                ui.add(
+                                    egui::TextEdit::singleline(ext)
+                                        .hint_text("md")
+                                        .desired_width(60.0),
+                                );
+                                ui.add(
+                                    egui::TextEdit::singleline(mime)
+                                        .hint_text("text/markdown")
+                                        .desired_width(200.0),
+                                );
+                                if ui.button(icon::CLOSE).on_hover_text("Remove").clicked() {
+                                    remove = Some(i);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            if let Some(i) = remove {
+                rows.remove(i);
+            }
+            ui.horizontal(|ui| {
+                if ui.button("+ Add").clicked() {
+                    rows.push(Default::default());
+                }
+                if ui.button("Save").clicked() {
+                    save = true;
+                }
+            });
+        });
+
+    if save {
+        is_show = false;
+        state.set_content_types(
+            state
+                .content_type_editor
+                .rows
+                .iter()
+                .map(|(ext, mime)| {
+                    (
+                        ext.trim().trim_start_matches('.').to_lowercase(),
+                        mime.trim().to_string(),
+                    )
+                })
+                .filter(|(ext, mime)| !ext.is_empty() && !mime.is_empty())
+                .collect(),
+        );
+    }
+    state.content_type_editor.is_show = is_show;
+}
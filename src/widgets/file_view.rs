@@ -1,5 +1,8 @@
 use crate::state::Update;
-use cc_storage::Object;
+use cc_storage::meta::ObjectMeta;
+use cc_storage::restore::RestoreState;
+use cc_storage::util::get_name_form_path;
+use cc_storage::{Object, Params, ServiceType};
 
 use super::confirm::ConfirmAction;
 use super::toasts::ToastKind;
@@ -16,10 +19,153 @@ fn zoomratio(i: f32, s: f32) -> f32 {
     i * s * 0.1
 }
 
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Disposition {
+    /// Whatever the object was uploaded with.
+    #[default]
+    Default,
+    Inline,
+    Attachment,
+}
+
+/// Query parameters of the generated link.
+#[derive(Default)]
+pub struct LinkParams {
+    pub is_show: bool,
+    pub disposition: Disposition,
+    /// File name of an attachment, the name of the object when empty.
+    pub filename: String,
+    pub content_type: String,
+    /// OSS `x-oss-process`, like `image/resize,w_200`.
+    pub process: String,
+    /// More parameters, as `key=value&key2`.
+    pub extra: String,
+}
+
+impl LinkParams {
+    pub fn to_params(&self, key: &str) -> Option<Params> {
+        let mut params = Params::new();
+        let disposition = match self.disposition {
+            Disposition::Default => None,
+            Disposition::Inline => Some("inline".to_string()),
+            Disposition::Attachment => {
+                let name = match self.filename.trim() {
+                    "" => get_name_form_path(key),
+                    name => name.to_string(),
+                };
+                Some(format!(
+                    "attachment; filename=\"{}\"",
+                    name.replace('"', "")
+                ))
+            }
+        };
+        if let Some(disposition) = disposition {
+            params.insert("response-content-disposition".into(), Some(disposition));
+        }
+        if !self.content_type.trim().is_empty() {
+            params.insert(
+                "response-content-type".into(),
+                Some(self.content_type.trim().to_string()),
+            );
+        }
+        if !self.process.trim().is_empty() {
+            params.insert(
+                "x-oss-process".into(),
+                Some(self.process.trim().to_string()),
+            );
+        }
+        for pair in self
+            .extra
+            .split('&')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        {
+            match pair.split_once('=') {
+                Some((k, v)) => params.insert(k.to_string(), Some(v.to_string())),
+                None => params.insert(pair.to_string(), None),
+            };
+        }
+        (!params.is_empty()).then_some(params)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, service: &ServiceType) {
+        egui::Grid::new("link_params_grid")
+            .num_columns(2)
+            .spacing([10.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Disposition:");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.disposition, Disposition::Default, "Default");
+                    ui.selectable_value(&mut self.disposition, Disposition::Inline, "Inline");
+                    ui.selectable_value(
+                        &mut self.disposition,
+                        Disposition::Attachment,
+                        "Attachment",
+                    );
+                });
+                ui.end_row();
+                if self.disposition == Disposition::Attachment {
+                    ui.label("File name:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filename)
+                            .hint_text("name of the object"),
+                    );
+                    ui.end_row();
+                }
+                ui.label("Content-Type:");
+                ui.add(egui::TextEdit::singleline(&mut self.content_type).hint_text("as uploaded"));
+                ui.end_row();
+                if *service == ServiceType::Oss {
+                    ui.label("Process:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.process)
+                            .hint_text("image/resize,w_200"),
+                    )
+                    .on_hover_text("x-oss-process of the image or video processing");
+                    ui.end_row();
+                }
+                ui.label("Query:");
+                ui.add(egui::TextEdit::singleline(&mut self.extra).hint_text("key=value&key2"))
+                    .on_hover_text("More query parameters, signed with the link");
+                ui.end_row();
+            });
+        ui.label(
+            egui::RichText::new("Generate the link again to apply the parameters.")
+                .small()
+                .weak(),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LinkKind {
+    /// The unsigned URL on the endpoint, for public-read buckets.
+    Public,
+    /// The unsigned URL on the custom domain or CDN of the session.
+    Cdn,
+    Signed,
+}
+
+impl LinkKind {
+    fn name(&self) -> &'static str {
+        match self {
+            LinkKind::Public => "Public",
+            LinkKind::Cdn => "CDN",
+            LinkKind::Signed => "Signed",
+        }
+    }
+}
+
 pub struct FileView {
     pub is_preview: bool,
     pub img_zoom: f32,
     pub img_default_zoom: f32,
+    pub link_params: LinkParams,
+    pub link_kind: LinkKind,
+    pub public_url: String,
+    pub cdn_url: Option<String>,
+    /// Empty until a signed link is generated.
+    pub signed_url: String,
 }
 
 impl FileView {
@@ -28,6 +174,37 @@ impl FileView {
             is_preview: false,
             img_zoom: 1.0,
             img_default_zoom: 1.0,
+            link_params: LinkParams::default(),
+            link_kind: LinkKind::Signed,
+            public_url: String::new(),
+            cdn_url: None,
+            signed_url: String::new(),
+        }
+    }
+
+    /// Links of a newly viewed object. Public-read buckets get the CDN link,
+    /// or the public one without a CDN, private ones a signed link.
+    pub fn set_links(&mut self, public_url: String, cdn_url: Option<String>, is_private: bool) {
+        self.link_kind = match (is_private, &cdn_url) {
+            (true, _) => LinkKind::Signed,
+            (false, Some(_)) => LinkKind::Cdn,
+            (false, None) => LinkKind::Public,
+        };
+        self.public_url = public_url;
+        self.cdn_url = cdn_url;
+        self.signed_url.clear();
+    }
+
+    pub fn set_signed_url(&mut self, url: String) {
+        self.signed_url = url;
+        self.link_kind = LinkKind::Signed;
+    }
+
+    fn link(&self) -> &str {
+        match self.link_kind {
+            LinkKind::Public => &self.public_url,
+            LinkKind::Cdn => self.cdn_url.as_deref().unwrap_or_default(),
+            LinkKind::Signed => &self.signed_url,
         }
     }
 
@@ -35,9 +212,11 @@ impl FileView {
         &mut self,
         ctx: &egui::Context,
         current_object: &Object,
+        current_meta: Option<&ObjectMeta>,
         current_file: Option<&cc_files::FileType>,
+        service: &ServiceType,
     ) {
-        let mut url = current_object.url();
+        let url = current_object.url();
         let win_size = ctx.input(|i| i.screen_rect).size();
         let frame = egui::Frame {
             fill: ctx.style().visuals.panel_fill,
@@ -123,6 +302,12 @@ impl FileView {
                                 file.show(ui);
                             });
                     }
+                } else if let Some(restore) = current_meta
+                    .map(|meta| &meta.restore)
+                    .filter(|restore| !restore.is_readable())
+                {
+                    ui.label(restore.describe());
+                    ui.label("The content can not be previewed or downloaded until restored.");
                 } else {
                     ui.centered_and_justified(|ui| ui.spinner());
                 }
@@ -149,37 +334,68 @@ impl FileView {
                         }
                     });
                 }
+                let link = self.link().to_string();
                 ui.horizontal(|ui| {
-                    ui.label(format!("{} Link:", icon::LINK));
+                    ui.label(icon::LINK);
+                    egui::ComboBox::from_id_source("link_kind")
+                        .width(60.0)
+                        .selected_text(self.link_kind.name())
+                        .show_ui(ui, |ui| {
+                            for kind in [LinkKind::Public, LinkKind::Cdn, LinkKind::Signed] {
+                                if kind == LinkKind::Cdn && self.cdn_url.is_none() {
+                                    continue;
+                                }
+                                ui.selectable_value(&mut self.link_kind, kind, kind.name());
+                            }
+                        });
+                    let is_signed = self.link_kind == LinkKind::Signed;
+                    let buttons = if is_signed { 3.0 } else { 1.0 };
                     ui.add(
-                        egui::TextEdit::singleline(&mut url)
-                            .desired_width(ui.available_width() - 50.0),
+                        egui::TextEdit::singleline(&mut link.as_str())
+                            .hint_text(if is_signed {
+                                "Generate a signed link"
+                            } else {
+                                ""
+                            })
+                            .desired_width(ui.available_width() - buttons * 25.0),
                     );
-                    if ui
-                        .button(icon::REFRESH)
-                        .on_hover_text("Generate Link")
-                        .clicked()
-                    {
-                        global()
-                            .update_tx
-                            .send(Update::Prompt((
-                                "Please enter the link expiration (in seconds):".to_string(),
-                                ConfirmAction::GenerateUrl(3600),
-                            )))
-                            .unwrap();
+                    if is_signed {
+                        if ui
+                            .selectable_label(self.link_params.is_show, icon::FILTER)
+                            .on_hover_text("Link Parameters")
+                            .clicked()
+                        {
+                            self.link_params.is_show = !self.link_params.is_show;
+                        }
+                        if ui
+                            .button(icon::REFRESH)
+                            .on_hover_text("Generate Link")
+                            .clicked()
+                        {
+                            global()
+                                .update_tx
+                                .send(Update::Prompt((
+                                    "Please enter the link expiration (in seconds):".to_string(),
+                                    ConfirmAction::GenerateUrl(3600),
+                                )))
+                                .unwrap();
+                        }
                     }
                     if ui
-                        .button(icon::CLIPBOARD)
+                        .add_enabled(!link.is_empty(), egui::Button::new(icon::CLIPBOARD))
                         .on_hover_text("Copy Link")
                         .clicked()
                     {
-                        ui.output_mut(|o| o.copied_text = url.to_string());
+                        ui.output_mut(|o| o.copied_text = link.clone());
                         global()
                             .update_tx
                             .send(Update::Toast(("Copied!".to_string(), ToastKind::Success)))
                             .unwrap();
                     }
                 });
+                if self.link_params.is_show && self.link_kind == LinkKind::Signed {
+                    self.link_params.ui(ui, service);
+                }
                 ui.horizontal(|ui| {
                     ui.label(format!(
                         "{} Size: {}",
@@ -192,6 +408,72 @@ impl FileView {
                         current_object.date_string()
                     ));
                 });
+                egui::CollapsingHeader::new("Properties")
+                    .id_source("object_properties")
+                    .show(ui, |ui| {
+                        let Some(meta) = current_meta else {
+                            ui.spinner();
+                            return;
+                        };
+                        ui.horizontal(|ui| {
+                            if ui.button("Edit Metadata").clicked() {
+                                global()
+                                    .update_tx
+                                    .send(Update::EditMeta(vec![current_object.key().to_string()]))
+                                    .unwrap();
+                            }
+                            if meta.tags.is_some() && ui.button("Edit Tags").clicked() {
+                                global()
+                                    .update_tx
+                                    .send(Update::EditTags(vec![current_object.key().to_string()]))
+                                    .unwrap();
+                            }
+                        });
+                        if meta.restore.is_archived() {
+                            ui.horizontal(|ui| {
+                                ui.label(meta.restore.describe());
+                                if meta.restore != RestoreState::Restoring
+                                    && ui.button("Restore").clicked()
+                                {
+                                    global()
+                                        .update_tx
+                                        .send(Update::Restore(vec![current_object
+                                            .key()
+                                            .to_string()]))
+                                        .unwrap();
+                                }
+                            });
+                        }
+                        if let Some(tags) = meta.tags.as_ref().filter(|tags| !tags.is_empty()) {
+                            ui.label("Tags");
+                            egui::Grid::new("tags_grid")
+                                .num_columns(2)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (key, value) in tags {
+                                        ui.label(key);
+                                        ui.label(value).on_hover_text(value);
+                                        ui.end_row();
+                                    }
+                                });
+                            ui.separator();
+                        }
+                        egui::ScrollArea::vertical()
+                            .id_source("properties_scroller")
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("properties_grid")
+                                    .num_columns(2)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (name, value) in &meta.headers {
+                                            ui.label(name);
+                                            ui.label(value).on_hover_text(value);
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                    });
             });
     }
 
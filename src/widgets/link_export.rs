@@ -0,0 +1,191 @@
+use crate::global;
+use crate::state::{State, Update};
+use crate::widgets::toasts::ToastKind;
+use cc_storage::link_export::{ExportFormat, SignedLinks};
+use cc_ui::icon;
+
+/// Lines of the export shown before it is copied or saved.
+const PREVIEW_LINES: usize = 200;
+
+pub struct LinkExport {
+    pub is_show: bool,
+    /// Selected objects and folders, or the folder to export.
+    pub keys: Vec<String>,
+    pub expire: u64,
+    pub format: ExportFormat,
+    pub loading: bool,
+    pub links: Option<SignedLinks>,
+    /// `links` rendered in `format`.
+    text: String,
+    /// Objects that could not be signed, listed in the logs.
+    pub failed: usize,
+}
+
+impl Default for LinkExport {
+    fn default() -> Self {
+        Self {
+            is_show: false,
+            keys: vec![],
+            expire: 24 * 3600,
+            format: ExportFormat::default(),
+            loading: false,
+            links: None,
+            text: String::new(),
+            failed: 0,
+        }
+    }
+}
+
+impl LinkExport {
+    /// Keep the expiry and format of the last export.
+    pub fn open(&mut self, keys: Vec<String>) {
+        self.is_show = true;
+        self.keys = keys;
+        self.loading = false;
+        self.links = None;
+        self.text.clear();
+        self.failed = 0;
+    }
+
+    pub fn set_links(&mut self, links: SignedLinks, failed: usize) {
+        self.loading = false;
+        self.links = Some(links);
+        self.failed = failed;
+        self.render();
+    }
+
+    fn render(&mut self) {
+        if let Some(links) = &self.links {
+            self.text = links.render(self.format);
+        }
+    }
+}
+
+pub fn link_export_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.link_export.is_show;
+    let mut generate = false;
+    let title = match state.link_export.keys.as_slice() {
+        [key] if key == "/" => "Export Links: /".to_string(),
+        [key] => format!("Export Links: {key}"),
+        keys => format!("Export Links: {} items", keys.len()),
+    };
+    egui::Window::new(title)
+        .id(egui::Id::new("link_export_dialog"))
+        .open(&mut is_show)
+        .default_width(520.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut state.link_export;
+            egui::Grid::new("link_export_grid")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Expires in:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut dialog.expire)
+                                .clamp_range(1..=7 * 24 * 3600)
+                                .speed(60),
+                        );
+                        ui.label("seconds");
+                    });
+                    ui.end_row();
+
+                    ui.label("Format:");
+                    ui.horizontal(|ui| {
+                        for format in ExportFormat::ALL {
+                            if ui
+                                .radio_value(&mut dialog.format, format, format.name())
+                                .changed()
+                            {
+                                dialog.render();
+                            }
+                        }
+                    });
+                    ui.end_row();
+                });
+
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!dialog.loading, |ui| {
+                    if ui.button(format!("{} Generate", icon::LINK)).clicked() {
+                        generate = true;
+                    }
+                });
+                if dialog.loading {
+                    ui.spinner();
+                }
+                if dialog.links.is_none() {
+                    return;
+                }
+                let text = &dialog.text;
+                if ui.button(format!("{} Copy", icon::CLIPBOARD)).clicked() {
+                    ui.output_mut(|o| o.copied_text.clone_from(text));
+                    global()
+                        .update_tx
+                        .send(Update::Toast(("Copied!".to_string(), ToastKind::Success)))
+                        .unwrap();
+                }
+                if ui.button(format!("{} Save", icon::DOWNLOAD)).clicked() {
+                    let file_name = format!("links.{}", dialog.format.extension());
+                    if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file()
+                    {
+                        let (message, kind) = match std::fs::write(&path, text) {
+                            Ok(_) => (format!("Saved to {}", path.display()), ToastKind::Success),
+                            Err(err) => (format!("Save failed: {err}"), ToastKind::Error),
+                        };
+                        global()
+                            .update_tx
+                            .send(Update::Toast((message, kind)))
+                            .unwrap();
+                    }
+                }
+            });
+
+            let Some(links) = &dialog.links else {
+                return;
+            };
+            let text = &dialog.text;
+            ui.label(format!(
+                "{} links, valid until {}",
+                links.links.len(),
+                links.expires_string()
+            ));
+            if dialog.failed > 0 {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "{} objects could not be signed, see the logs.",
+                        dialog.failed
+                    ),
+                );
+            }
+            ui.separator();
+            let mut preview: String = text
+                .lines()
+                .take(PREVIEW_LINES)
+                .collect::<Vec<_>>()
+                .join("\n");
+            if text.lines().nth(PREVIEW_LINES).is_some() {
+                preview.push_str("\n…");
+            }
+            egui::ScrollArea::both()
+                .max_height(300.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut preview.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+        });
+
+    if generate {
+        let dialog = &mut state.link_export;
+        dialog.loading = true;
+        let (keys, expire) = (dialog.keys.clone(), dialog.expire);
+        state.export_links(keys, expire);
+    }
+    state.link_export.is_show = is_show;
+}
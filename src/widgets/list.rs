@@ -9,9 +9,17 @@ pub fn list_ui(state: &mut State, ui: &mut egui::Ui, row_range: std::ops::Range<
         .num_columns(1)
         .striped(true)
         .show(ui, |ui| {
-            for data in state.list[row_range].iter_mut() {
+            for (i, ranges) in state.view[row_range].iter() {
+                let name = state.display_name(&state.list[*i]).into_owned();
+                // Ranges are found in the file name, at the end of the path.
+                let offset = name.len() - state.list[*i].name().len().min(name.len());
+                let ranges: Vec<_> = ranges
+                    .iter()
+                    .map(|r| r.start + offset..r.end + offset)
+                    .collect();
+                let data = &mut state.list[*i];
                 let is_current = data.key() == state.current_object.key();
-                let response = list_item_ui(ui, data, is_current);
+                let response = list_item_ui(ui, data, &name, &ranges, is_current);
                 if response.on_hover_text(data.name()).clicked() {
                     handle_click(data);
                 }
@@ -36,10 +44,11 @@ pub fn thumb_ui(
         .show(ui, |ui| {
             for i in row_range {
                 for j in 0..num_cols {
-                    if let Some(data) = state.list.get_mut(j + i * num_cols) {
+                    if let Some((index, ranges)) = state.view.get(j + i * num_cols) {
+                        let data = &mut state.list[*index];
                         egui::Frame::none().show(ui, |ui| {
                             let is_current = data.key() == state.current_object.key();
-                            let response = thumb_item_ui(ui, data, is_current);
+                            let response = thumb_item_ui(ui, data, ranges, is_current);
                             if response.on_hover_text(data.name()).clicked() {
                                 handle_click(data);
                             }
@@ -4,8 +4,15 @@ use crate::widgets::confirm::ConfirmAction;
 use cc_storage::Object;
 use cc_ui::icon;
 use egui::{self, vec2, Color32, Frame, Margin, Sense, WidgetInfo, WidgetType};
+use std::ops::Range;
 
-pub fn list_item_ui(ui: &mut egui::Ui, data: &mut Object, is_current: bool) -> egui::Response {
+pub fn list_item_ui(
+    ui: &mut egui::Ui,
+    data: &mut Object,
+    name: &str,
+    highlight: &[Range<usize>],
+    is_current: bool,
+) -> egui::Response {
     let row_height = ui.text_style_height(&egui::TextStyle::Body);
     let initial_size = vec2(
         ui.available_width(),
@@ -63,11 +70,19 @@ pub fn list_item_ui(ui: &mut egui::Ui, data: &mut Object, is_current: bool) -> e
                         ui.set_width(60.);
                         ui.label(data.size_string());
                     });
+                    egui::Frame::none().show(ui, |ui| {
+                        ui.set_width(90.);
+                        let class = data.storage_class();
+                        if !class.is_empty() {
+                            ui.label(egui::RichText::new(class).small().weak())
+                                .on_hover_text("Storage class");
+                        }
+                    });
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
                         ui.checkbox(&mut data.selected, "");
                         ui.vertical(|ui| {
                             ui.add(egui::Label::new(
-                                global().cc_ui.text_ellipsis(data.name().as_ref(), 1),
+                                global().cc_ui.text_highlight(name, highlight, 1),
                             ));
                         });
                     });
@@ -1,5 +1,6 @@
 use crate::global;
 use crate::state::State;
+use cc_core::log::LogState;
 
 pub fn log_panel_ui(ctx: &egui::Context, state: &mut State) {
     let frame = egui::Frame {
@@ -23,10 +24,15 @@ pub fn log_panel_ui(ctx: &egui::Context, state: &mut State) {
                             // tracing::info!("row_range: {:?}", row_range);
                             for i in row_range {
                                 if let Some(data) = state.logs.get(i) {
-                                    ui.label(egui::RichText::new(format!(
+                                    let text = egui::RichText::new(format!(
                                         "{:?}: {}",
                                         data.log_type, data.data
-                                    )));
+                                    ));
+                                    ui.label(match data.state {
+                                        LogState::Error => text.color(ui.visuals().error_fg_color),
+                                        LogState::Warn => text.color(ui.visuals().warn_fg_color),
+                                        _ => text,
+                                    });
                                 }
                             }
                         });
@@ -0,0 +1,168 @@
+use crate::state::State;
+use cc_storage::meta::{MetaPatch, ObjectMeta, SYSTEM_HEADERS};
+use cc_ui::icon;
+
+#[derive(Default)]
+pub struct MetaEditor {
+    pub is_show: bool,
+    pub keys: Vec<String>,
+    /// Waiting for the metadata of a single object.
+    pub loading: bool,
+    /// Header, value and whether a bulk edit sets it.
+    pub system: Vec<(&'static str, String, bool)>,
+    pub user: Vec<(String, String)>,
+    /// Comma separated user keys removed by a bulk edit.
+    pub user_remove: String,
+}
+
+impl MetaEditor {
+    pub fn open(&mut self, keys: Vec<String>) {
+        *self = MetaEditor {
+            is_show: true,
+            system: SYSTEM_HEADERS
+                .iter()
+                .map(|name| (*name, String::new(), false))
+                .collect(),
+            ..Default::default()
+        };
+        self.keys = keys;
+        self.loading = !self.is_bulk();
+    }
+
+    /// A single object is edited as a whole, anything else as a patch of the
+    /// checked fields.
+    pub fn is_bulk(&self) -> bool {
+        !matches!(self.keys.as_slice(), [key] if !key.ends_with('/'))
+    }
+
+    pub fn set_meta(&mut self, meta: &ObjectMeta) {
+        self.loading = false;
+        self.system = meta
+            .system
+            .iter()
+            .map(|(name, value)| (*name, value.clone(), true))
+            .collect();
+        self.user = meta.user.clone();
+    }
+
+    pub fn patch(&self) -> MetaPatch {
+        MetaPatch {
+            system: self
+                .system
+                .iter()
+                .filter(|(_, _, apply)| *apply)
+                .map(|(name, value, _)| (*name, value.trim().to_string()))
+                .collect(),
+            user_set: self.user.clone(),
+            user_remove: self
+                .user_remove
+                .split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            replace_user: !self.is_bulk(),
+        }
+    }
+}
+
+pub fn meta_editor_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.meta_editor.is_show;
+    let mut save = false;
+    let title = match state.meta_editor.keys.as_slice() {
+        [key] => format!("Metadata: {key}"),
+        keys => format!("Metadata: {} items", keys.len()),
+    };
+    egui::Window::new(title)
+        .id(egui::Id::new("meta_editor"))
+        .open(&mut is_show)
+        .default_width(420.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let editor = &mut state.meta_editor;
+            if editor.loading {
+                ui.spinner();
+                return;
+            }
+            let is_bulk = editor.is_bulk();
+            if is_bulk {
+                ui.label("Only the checked headers are changed on every object.");
+            }
+            egui::Grid::new("meta_system")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    for (name, value, apply) in editor.system.iter_mut() {
+                        if is_bulk {
+                            ui.checkbox(apply, *name);
+                        } else {
+                            ui.label(*name);
+                        }
+                        let resp =
+                            ui.add(egui::TextEdit::singleline(value).desired_width(f32::INFINITY));
+                        if resp.changed() {
+                            *apply = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+            ui.label(if is_bulk {
+                "User metadata to add or replace:"
+            } else {
+                "User metadata:"
+            });
+            let mut remove = None;
+            egui::Grid::new("meta_user")
+                .num_columns(3)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    for (i, (key, value)) in editor.user.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(key)
+                                .hint_text("key")
+                                .desired_width(120.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(value)
+                                .hint_text("value")
+                                .desired_width(200.0),
+                        );
+                        if ui.button(icon::CLOSE).on_hover_text("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                editor.user.remove(i);
+            }
+            if ui.button("+ Add").clicked() {
+                editor.user.push(Default::default());
+            }
+            if is_bulk {
+                ui.horizontal(|ui| {
+                    ui.label("Remove keys:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut editor.user_remove)
+                            .hint_text("key1, key2")
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            }
+
+            ui.separator();
+            if ui.button("Save").clicked() {
+                save = true;
+            }
+        });
+
+    if save {
+        is_show = false;
+        let keys = state.meta_editor.keys.clone();
+        let patch = state.meta_editor.patch();
+        state.save_meta(keys, patch);
+    }
+    state.meta_editor.is_show = is_show;
+}
@@ -1,24 +1,42 @@
 // mod action_bar;
+mod bucket_panel;
 pub mod confirm;
+pub mod content_types;
 mod file_view;
+pub mod link_export;
 pub mod list;
 mod list_item;
 mod location_bar;
 mod log_panel;
+pub mod meta_editor;
 mod password;
+pub mod restore_dialog;
+pub mod search_panel;
 mod status_bar;
+pub mod tag_editor;
 mod thumb_item;
 pub mod toasts;
 mod top_bar;
 mod transfer_panel;
+pub mod upload_link;
+pub mod versions_panel;
 
 // pub use action_bar::action_bar_ui;
+pub use bucket_panel::bucket_panel_ui;
+pub use content_types::content_types_ui;
 pub use file_view::FileView;
+pub use link_export::link_export_ui;
 pub use list_item::list_item_ui;
 pub use location_bar::location_bar_ui;
 pub use log_panel::log_panel_ui;
+pub use meta_editor::meta_editor_ui;
 pub use password::password;
+pub use restore_dialog::restore_dialog_ui;
+pub use search_panel::search_panel_ui;
 pub use status_bar::status_bar_ui;
+pub use tag_editor::tag_editor_ui;
 pub use thumb_item::thumb_item_ui;
 pub use top_bar::top_bar_ui;
 pub use transfer_panel::transfer_panel_ui;
+pub use upload_link::upload_link_ui;
+pub use versions_panel::versions_panel_ui;
@@ -0,0 +1,94 @@
+use crate::state::State;
+use cc_core::ServiceType;
+
+pub struct RestoreDialog {
+    pub is_show: bool,
+    pub keys: Vec<String>,
+    /// How long the restored copy stays readable.
+    pub days: u32,
+    pub tier: String,
+    /// Tiers the selected objects take.
+    pub tiers: Vec<&'static str>,
+}
+
+impl Default for RestoreDialog {
+    fn default() -> Self {
+        Self {
+            is_show: false,
+            keys: vec![],
+            days: 1,
+            tier: String::new(),
+            tiers: vec![],
+        }
+    }
+}
+
+impl RestoreDialog {
+    /// Keep the days and tier of the last restore.
+    pub fn open(&mut self, keys: Vec<String>, tiers: Vec<&'static str>) {
+        self.is_show = true;
+        self.keys = keys;
+        if !tiers.contains(&self.tier.as_str()) {
+            // The standard tier, the fastest one costs the most.
+            let tier = tiers.iter().find(|t| **t == "Standard").or(tiers.first());
+            self.tier = tier.unwrap_or(&"").to_string();
+        }
+        self.tiers = tiers;
+    }
+}
+
+pub fn restore_dialog_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.restore_dialog.is_show;
+    let mut restore = false;
+    let title = match state.restore_dialog.keys.as_slice() {
+        [key] => format!("Restore: {key}"),
+        keys => format!("Restore: {} items", keys.len()),
+    };
+    let is_azblob = state.session.service == ServiceType::Azblob;
+    egui::Window::new(title)
+        .id(egui::Id::new("restore_dialog"))
+        .open(&mut is_show)
+        .default_width(320.0)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut state.restore_dialog;
+            egui::Grid::new("restore_grid")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    if !is_azblob {
+                        ui.label("Days:");
+                        ui.add(egui::DragValue::new(&mut dialog.days).clamp_range(1..=365))
+                            .on_hover_text("How long the restored copy stays readable");
+                        ui.end_row();
+                    }
+                    ui.label(if is_azblob { "Priority:" } else { "Tier:" });
+                    egui::ComboBox::from_id_source("restore_tier")
+                        .selected_text(dialog.tier.as_str())
+                        .show_ui(ui, |ui| {
+                            for tier in &dialog.tiers {
+                                ui.selectable_value(&mut dialog.tier, tier.to_string(), *tier);
+                            }
+                        });
+                    ui.end_row();
+                });
+            if is_azblob {
+                ui.label("The blobs are moved back to the hot tier.");
+            }
+            ui.label("A restore can take from minutes to hours depending on the tier.");
+
+            ui.separator();
+            if ui.button("Restore").clicked() {
+                restore = true;
+            }
+        });
+
+    if restore {
+        is_show = false;
+        let dialog = &state.restore_dialog;
+        let (keys, days, tier) = (dialog.keys.clone(), dialog.days, dialog.tier.clone());
+        state.restore_objects(keys, days, tier);
+    }
+    state.restore_dialog.is_show = is_show;
+}
@@ -0,0 +1,196 @@
+use crate::global;
+use crate::state::{NavgatorType, State, Update};
+use cc_storage::filter::{Filter, FilterMode};
+use cc_storage::search::{CancelToken, SearchQuery};
+use cc_storage::Object;
+use cc_ui::icon;
+
+#[derive(Default)]
+pub struct SearchPanel {
+    pub is_show: bool,
+    pub pattern: String,
+    pub mode: FilterMode,
+    /// Search below the current path instead of the whole bucket.
+    pub in_current_folder: bool,
+    pub min_size: String,
+    pub max_size: String,
+    pub modified_after: String,
+    pub modified_before: String,
+    pub results: Vec<Object>,
+    pub scanned: usize,
+    pub running: Option<CancelToken>,
+    /// Incremented by every search, to drop results of the previous ones.
+    pub generation: usize,
+    pub err: Option<String>,
+}
+
+impl SearchPanel {
+    pub fn query(&self, current_path: &str) -> cc_storage::Result<SearchQuery> {
+        let filter = if self.pattern.is_empty() {
+            None
+        } else {
+            Some(Filter::new(self.mode, &self.pattern)?)
+        };
+
+        Ok(SearchQuery {
+            prefix: if self.in_current_folder {
+                current_path.to_string()
+            } else {
+                String::new()
+            },
+            filter,
+            min_size: SearchQuery::parse_size(&self.min_size)?,
+            max_size: SearchQuery::parse_size(&self.max_size)?,
+            modified_after: SearchQuery::parse_date(&self.modified_after, false)?,
+            modified_before: SearchQuery::parse_date(&self.modified_before, true)?,
+        })
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+}
+
+pub fn search_panel_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.search.is_show;
+    egui::Window::new("Search")
+        .open(&mut is_show)
+        .default_width(520.0)
+        .default_height(420.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("search_form")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    let search = &mut state.search;
+                    ui.label("Name:");
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("search_mode")
+                            .selected_text(search.mode.name())
+                            .width(80.0)
+                            .show_ui(ui, |ui| {
+                                for m in FilterMode::ALL {
+                                    ui.selectable_value(&mut search.mode, m, m.name());
+                                }
+                            });
+                        ui.add(
+                            egui::TextEdit::singleline(&mut search.pattern)
+                                .hint_text("*.jpg")
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label("Size:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut search.min_size)
+                                .hint_text("min, e.g. 1 MB")
+                                .desired_width(120.0),
+                        );
+                        ui.label("-");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut search.max_size)
+                                .hint_text("max")
+                                .desired_width(120.0),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label("Modified:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut search.modified_after)
+                                .hint_text("from YYYY-MM-DD")
+                                .desired_width(120.0),
+                        );
+                        ui.label("-");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut search.modified_before)
+                                .hint_text("to YYYY-MM-DD")
+                                .desired_width(120.0),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label("");
+                    ui.checkbox(
+                        &mut search.in_current_folder,
+                        format!("Only in current folder: /{}", state.current_path),
+                    );
+                    ui.end_row();
+                });
+
+            ui.horizontal(|ui| {
+                if state.search.is_running() {
+                    if ui.button(format!("{} Stop", icon::PAUSE)).clicked() {
+                        state.stop_search();
+                    }
+                    ui.spinner();
+                } else if ui.button(format!("{} Search", icon::PLAY)).clicked() {
+                    state.start_search();
+                }
+                ui.label(format!(
+                    "Found {} in {} keys",
+                    state.search.results.len(),
+                    state.search.scanned
+                ));
+            });
+            if let Some(err) = &state.search.err {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            ui.separator();
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+            egui::ScrollArea::vertical()
+                .id_source("search_results")
+                .auto_shrink([false; 2])
+                .show_rows(
+                    ui,
+                    row_height,
+                    state.search.results.len(),
+                    |ui, row_range| {
+                        for obj in state.search.results[row_range].iter() {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button(icon::FORWARD)
+                                    .on_hover_text("Open containing folder")
+                                    .clicked()
+                                {
+                                    let key = obj.key();
+                                    let folder = &key[..key.len() - obj.name().len()];
+                                    global()
+                                        .update_tx
+                                        .send(Update::Navgator(NavgatorType::New(
+                                            folder.to_string(),
+                                        )))
+                                        .unwrap();
+                                }
+                                if ui
+                                    .link(global().cc_ui.text_ellipsis(obj.key(), 1))
+                                    .on_hover_text(format!(
+                                        "{}\n{}\n{}",
+                                        obj.key(),
+                                        obj.size_string(),
+                                        obj.date_string()
+                                    ))
+                                    .clicked()
+                                {
+                                    global()
+                                        .update_tx
+                                        .send(Update::ViewObject(obj.clone()))
+                                        .unwrap();
+                                }
+                            });
+                        }
+                    },
+                );
+        });
+
+    if !is_show {
+        state.stop_search();
+    }
+    state.search.is_show = is_show;
+}
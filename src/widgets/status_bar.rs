@@ -21,8 +21,14 @@ pub fn status_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe
                         "{} - Selected: {}/{}",
                         state.session.service,
                         state.selected_item,
-                        state.list.len()
+                        state.view.len()
                     ));
+                    if let Some(bucket) = &state.bucket {
+                        let text = format!("{} ({})", state.session.bucket, bucket.grant().name());
+                        if ui.button(text).on_hover_text("Bucket info").clicked() {
+                            state.is_show_bucket = !state.is_show_bucket;
+                        }
+                    }
                 }
 
                 if state.loading_more {
@@ -73,6 +79,14 @@ pub fn status_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe
                             )))
                             .unwrap();
                     }
+                    if ui
+                        .button(egui::RichText::new("MIME").color(n_color))
+                        .on_hover_text("Content-Type of uploads")
+                        .clicked()
+                    {
+                        let rows = state.setting.content_types.clone().into_iter();
+                        state.content_type_editor.toggle(rows);
+                    }
                     if ui
                         .button(egui::RichText::new(icon::LOG).color(color))
                         .on_hover_text("Logs")
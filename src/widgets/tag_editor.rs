@@ -0,0 +1,128 @@
+use crate::state::State;
+use cc_storage::tagging::{TagPatch, Tags};
+use cc_ui::icon;
+
+#[derive(Default)]
+pub struct TagEditor {
+    pub is_show: bool,
+    pub keys: Vec<String>,
+    /// Waiting for the tags of a single object.
+    pub loading: bool,
+    pub tags: Tags,
+    /// Comma separated tag keys removed by a bulk edit.
+    pub remove: String,
+    /// Replace all the tags of every object in a bulk edit.
+    pub replace: bool,
+}
+
+impl TagEditor {
+    pub fn open(&mut self, keys: Vec<String>) {
+        *self = TagEditor {
+            is_show: true,
+            ..Default::default()
+        };
+        self.keys = keys;
+        self.loading = !self.is_bulk();
+    }
+
+    /// A single object is edited as a whole, anything else as a patch.
+    pub fn is_bulk(&self) -> bool {
+        !matches!(self.keys.as_slice(), [key] if !key.ends_with('/'))
+    }
+
+    pub fn set_tags(&mut self, tags: &Tags) {
+        self.loading = false;
+        self.tags = tags.clone();
+    }
+
+    pub fn patch(&self) -> TagPatch {
+        TagPatch {
+            set: self.tags.clone(),
+            remove: self
+                .remove
+                .split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            replace: !self.is_bulk() || self.replace,
+        }
+    }
+}
+
+pub fn tag_editor_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.tag_editor.is_show;
+    let mut save = false;
+    let title = match state.tag_editor.keys.as_slice() {
+        [key] => format!("Tags: {key}"),
+        keys => format!("Tags: {} items", keys.len()),
+    };
+    egui::Window::new(title)
+        .id(egui::Id::new("tag_editor"))
+        .open(&mut is_show)
+        .default_width(380.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let editor = &mut state.tag_editor;
+            if editor.loading {
+                ui.spinner();
+                return;
+            }
+            let is_bulk = editor.is_bulk();
+            if is_bulk {
+                ui.label("Tags to add or replace on every object:");
+            }
+            let mut remove = None;
+            egui::Grid::new("tag_rows")
+                .num_columns(3)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    for (i, (key, value)) in editor.tags.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(key)
+                                .hint_text("key")
+                                .desired_width(120.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(value)
+                                .hint_text("value")
+                                .desired_width(180.0),
+                        );
+                        if ui.button(icon::CLOSE).on_hover_text("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                editor.tags.remove(i);
+            }
+            if ui.button("+ Add").clicked() {
+                editor.tags.push(Default::default());
+            }
+            if is_bulk {
+                ui.horizontal(|ui| {
+                    ui.label("Remove keys:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut editor.remove)
+                            .hint_text("key1, key2")
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+                ui.checkbox(&mut editor.replace, "Replace all existing tags");
+            }
+
+            ui.separator();
+            if ui.button("Save").clicked() {
+                save = true;
+            }
+        });
+
+    if save {
+        is_show = false;
+        let keys = state.tag_editor.keys.clone();
+        let patch = state.tag_editor.patch();
+        state.save_tags(keys, patch);
+    }
+    state.tag_editor.is_show = is_show;
+}
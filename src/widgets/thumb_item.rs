@@ -1,10 +1,16 @@
 use crate::global;
 use cc_storage::Object;
-use cc_ui::text_ellipsis;
+use cc_ui::text_highlight;
 use cc_ui::THUMB_LIST_HEIGHT;
 use egui::{self, vec2, RichText, Sense, WidgetInfo, WidgetType};
+use std::ops::Range;
 
-pub fn thumb_item_ui(ui: &mut egui::Ui, data: &mut Object, is_current: bool) -> egui::Response {
+pub fn thumb_item_ui(
+    ui: &mut egui::Ui,
+    data: &mut Object,
+    highlight: &[Range<usize>],
+    is_current: bool,
+) -> egui::Response {
     let initial_size = vec2(
         ui.available_width(),
         THUMB_LIST_HEIGHT, // Assume there will be
@@ -43,11 +49,12 @@ pub fn thumb_item_ui(ui: &mut egui::Ui, data: &mut Object, is_current: bool) ->
                         ui.checkbox(&mut data.selected, "");
                     });
                     ui.vertical(|ui| {
-                        ui.label(text_ellipsis(ui.style(), &data.name(), 1));
-                        ui.label(
-                            RichText::new(data.size_string())
-                                .color(ui.style().visuals.weak_text_color()),
-                        );
+                        ui.label(text_highlight(ui.style(), &data.name(), highlight, 1));
+                        let mut detail = data.size_string();
+                        if !data.storage_class().is_empty() {
+                            detail = format!("{detail} · {}", data.storage_class());
+                        }
+                        ui.label(RichText::new(detail).color(ui.style().visuals.weak_text_color()));
                     });
                 });
             });
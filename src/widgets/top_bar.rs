@@ -1,9 +1,9 @@
 use super::confirm::ConfirmAction;
 use super::location_bar_ui;
 use crate::global;
-use crate::state::{FileAction, NavgatorType, Route, State, Status, Update};
+use crate::state::{FileAction, NavgatorType, Route, SortKey, State, Status, Update};
 use cc_core::ShowType;
-use cc_storage::util::get_name_form_path;
+use cc_storage::filter::FilterMode;
 use cc_ui::icon;
 
 pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::Frame) {
@@ -115,6 +115,53 @@ pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::F
                                 {
                                     state.scroll_top = true;
                                 }
+                                if ui
+                                    .selectable_value(
+                                        &mut state.setting.show_type,
+                                        ShowType::Flat,
+                                        egui::RichText::new("Flat").color(
+                                            if show_type == ShowType::Flat {
+                                                active_color
+                                            } else {
+                                                normal_color
+                                            },
+                                        ),
+                                    )
+                                    .on_hover_text("All objects below this folder")
+                                    .clicked()
+                                {
+                                    state.scroll_top = true;
+                                }
+                                if (show_type == ShowType::Flat)
+                                    != (state.setting.show_type == ShowType::Flat)
+                                {
+                                    state.refresh();
+                                }
+
+                                ui.separator();
+                                for (key, label) in
+                                    [(SortKey::Date, icon::DATE), (SortKey::Size, icon::SIZE)]
+                                {
+                                    let (text, color) = match state.sort {
+                                        Some((k, true)) if k == key => {
+                                            (format!("{label}↓"), active_color)
+                                        }
+                                        Some((k, false)) if k == key => {
+                                            (format!("{label}↑"), active_color)
+                                        }
+                                        _ => (label.to_string(), normal_color),
+                                    };
+                                    if ui
+                                        .button(egui::RichText::new(text).color(color))
+                                        .on_hover_text(match key {
+                                            SortKey::Date => "Sort by date",
+                                            SortKey::Size => "Sort by size",
+                                        })
+                                        .clicked()
+                                    {
+                                        state.toggle_sort(key);
+                                    }
+                                }
                             });
                             location_bar_ui(ui, state);
                         });
@@ -126,6 +173,22 @@ pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::F
                                 state.picked_path = paths;
                             }
                         }
+                        if ui
+                            .button(format!("{} Upload Folder", icon::UPLOAD))
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                state.picked_path = vec![path];
+                            }
+                        }
+                        if ui
+                            .button(format!("{} Share Upload Link", icon::LINK))
+                            .on_hover_text("Let others upload without credentials")
+                            .clicked()
+                        {
+                            let path = state.current_path.clone();
+                            state.upload_link.open(&path);
+                        }
                         // create folder button
                         if ui
                             .button(format!("{} Create Folder", icon::CREATE_FOLDER))
@@ -141,20 +204,23 @@ pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::F
                         }
                         ui.separator();
                         ui.add_enabled_ui(
-                            state.selected_item == 1 && state.file_action.is_none(),
+                            state.selected_item > 0 && state.file_action.is_none(),
                             |ui| {
                                 if ui.button(format!("{} Copy", icon::COPY)).clicked() {
-                                    if let Some(obj) = state.list.iter().find(|x| x.selected) {
-                                        state.file_action =
-                                            Some(FileAction::Copy(obj.key().to_string()));
-                                    }
+                                    let keys = state.selected_keys();
+                                    state.file_action =
+                                        Some(FileAction::Copy(state.client().clone(), keys));
                                 }
                                 if ui.button(format!("{} Move", icon::MOVE)).clicked() {
-                                    if let Some(obj) = state.list.iter().find(|x| x.selected) {
-                                        state.file_action =
-                                            Some(FileAction::Move(obj.key().to_string()));
-                                    }
+                                    let keys = state.selected_keys();
+                                    state.file_action =
+                                        Some(FileAction::Move(state.client().clone(), keys));
                                 }
+                            },
+                        );
+                        ui.add_enabled_ui(
+                            state.selected_item == 1 && state.file_action.is_none(),
+                            |ui| {
                                 if ui.button(format!("{} Rename", icon::RENAME)).clicked() {
                                     if let Some(obj) = state.list.iter().find(|x| x.selected) {
                                         global()
@@ -185,13 +251,118 @@ pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::F
                                 }
                             },
                         );
+                        ui.add_enabled_ui(state.selected_item > 0, |ui| {
+                            if ui
+                                .button("Metadata")
+                                .on_hover_text("Edit the metadata of selected items")
+                                .clicked()
+                            {
+                                let keys = state.selected_keys();
+                                global().update_tx.send(Update::EditMeta(keys)).unwrap();
+                            }
+                        });
+                        ui.add_enabled_ui(
+                            state.selected_item > 0 && state.client().supports_tagging(),
+                            |ui| {
+                                if ui
+                                    .button("Tags")
+                                    .on_hover_text("Edit the tags of selected items")
+                                    .clicked()
+                                {
+                                    let keys = state.selected_keys();
+                                    global().update_tx.send(Update::EditTags(keys)).unwrap();
+                                }
+                            },
+                        );
+                        ui.add_enabled_ui(
+                            state.selected_item > 0 && state.file_action.is_none(),
+                            |ui| {
+                                ui.menu_button("Storage Class", |ui| {
+                                    for class in state.client().storage_classes() {
+                                        if ui.button(*class).clicked() {
+                                            let keys = state.selected_keys();
+                                            global()
+                                                .update_tx
+                                                .send(Update::Confirm((
+                                                    format!(
+                                                        "Do you confirm to move selected items to {class}?"
+                                                    ),
+                                                    ConfirmAction::SetStorageClass((
+                                                        keys,
+                                                        class.to_string(),
+                                                    )),
+                                                )))
+                                                .unwrap();
+                                            ui.close_menu();
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Change the storage class of selected items");
+                            },
+                        );
+                        if ui
+                            .button(format!("{} Export Links", icon::LINK))
+                            .on_hover_text("Signed links of the selected items, or of this folder")
+                            .clicked()
+                        {
+                            let mut keys = state.selected_keys();
+                            if keys.is_empty() {
+                                keys.push(match state.current_path.as_str() {
+                                    "" => "/".to_string(),
+                                    path if path.ends_with('/') => path.to_string(),
+                                    path => format!("{path}/"),
+                                });
+                            }
+                            state.link_export.open(keys);
+                        }
+                        ui.add_enabled_ui(
+                            state.selected_item > 0 && !state.client().restore_tiers().is_empty(),
+                            |ui| {
+                                if ui
+                                    .button("Restore")
+                                    .on_hover_text("Restore the archived selected items")
+                                    .clicked()
+                                {
+                                    let keys = state.selected_keys();
+                                    global().update_tx.send(Update::Restore(keys)).unwrap();
+                                }
+                            },
+                        );
+                        ui.add_enabled_ui(state.selected_item <= 1, |ui| {
+                            if ui
+                                .button("Versions")
+                                .on_hover_text(
+                                    "Versions of the selected item, or of the current folder",
+                                )
+                                .clicked()
+                            {
+                                let key = match state.list.iter().find(|x| x.selected) {
+                                    Some(obj) => obj.key().to_string(),
+                                    None if state.current_path.is_empty()
+                                        || state.current_path.ends_with('/') =>
+                                    {
+                                        state.current_path.clone()
+                                    }
+                                    None => format!("{}/", state.current_path),
+                                };
+                                state.show_versions(key);
+                            }
+                        });
+                        if ui
+                            .button(format!("{} Search", icon::CROSS_HAIR))
+                            .on_hover_text("Search the bucket recursively")
+                            .clicked()
+                        {
+                            state.search.is_show = !state.search.is_show;
+                        }
                         ui.add_visible_ui(state.file_action.is_some(), |ui| {
                             let text = match &state.file_action {
                                 Some(action) => match action {
-                                    FileAction::Copy(_) => "Paste",
-                                    FileAction::Move(_) => "Move",
+                                    FileAction::Copy(_, keys) => format!("Paste {}", keys.len()),
+                                    FileAction::Move(_, keys) => format!("Move {}", keys.len()),
                                 },
-                                None => "",
+                                None => String::new(),
                             };
                             ui.horizontal(|ui| {
                                 if ui
@@ -202,26 +373,7 @@ pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::F
                                     .on_hover_text("Paste to current directory")
                                     .clicked()
                                 {
-                                    if let Some(action) = &state.file_action {
-                                        match action {
-                                            FileAction::Copy(src) => {
-                                                let dest = format!(
-                                                    "{}{}",
-                                                    state.current_path,
-                                                    get_name_form_path(src)
-                                                );
-                                                state.copy_object(src.to_string(), dest, false);
-                                            }
-                                            FileAction::Move(src) => {
-                                                let dest = format!(
-                                                    "{}{}",
-                                                    state.current_path,
-                                                    get_name_form_path(src)
-                                                );
-                                                state.copy_object(src.to_string(), dest, true);
-                                            }
-                                        }
-                                    }
+                                    state.paste(state.current_path.clone(), false);
                                 }
                                 if ui.button(icon::CLOSE).on_hover_text("Cancel").clicked() {
                                     state.file_action = None;
@@ -229,12 +381,43 @@ pub fn top_bar_ui(ctx: &egui::Context, state: &mut State, _frame: &mut eframe::F
                             });
                         });
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                            let response = ui.add_sized(
-                                ui.available_size() - [20.0, 0.0].into(),
-                                egui::TextEdit::singleline(&mut state.filter_str)
-                                    .hint_text("Filter with file name")
-                                    .lock_focus(false),
-                            );
+                            let mode = state.filter_mode;
+                            egui::ComboBox::from_id_source("filter_mode")
+                                .selected_text(mode.name())
+                                .width(80.0)
+                                .show_ui(ui, |ui| {
+                                    for m in FilterMode::ALL {
+                                        ui.selectable_value(&mut state.filter_mode, m, m.name());
+                                    }
+                                });
+                            if mode != state.filter_mode {
+                                state.filter();
+                            }
+
+                            if state.client().supports_tagging() {
+                                let response = ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut state.tag_filter_str)
+                                            .hint_text("tag key=value")
+                                            .desired_width(100.0),
+                                    )
+                                    .on_hover_text("Only show objects with this tag");
+                                if response.changed() {
+                                    state.filter_by_tag();
+                                }
+                            }
+
+                            let mut text_edit = egui::TextEdit::singleline(&mut state.filter_str)
+                                .hint_text("Filter with file name")
+                                .lock_focus(false);
+                            if state.filter_err.is_some() {
+                                text_edit = text_edit.text_color(ui.visuals().error_fg_color);
+                            }
+                            let mut response =
+                                ui.add_sized(ui.available_size() - [20.0, 0.0].into(), text_edit);
+                            if let Some(err) = &state.filter_err {
+                                response = response.on_hover_text(err);
+                            }
 
                             if response.changed() {
                                 response.request_focus();
@@ -38,6 +38,11 @@ pub fn transfer_panel_ui(ctx: &egui::Context, state: &mut State) {
                             "upload".into(),
                             egui::RichText::new("Upload"),
                         );
+                        ui.selectable_value(
+                            &mut state.transfer_manager.t_type,
+                            "task".into(),
+                            egui::RichText::new("Task"),
+                        );
                     });
                     ui.add_space(10.0);
                     egui::ScrollArea::vertical()
@@ -0,0 +1,225 @@
+use crate::state::State;
+use crate::widgets::toasts::ToastKind;
+use crate::{global, state::Update};
+use cc_storage::post_policy::PostPolicyParams;
+use cc_storage::search::SearchQuery;
+use cc_ui::icon;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum UploadLinkMode {
+    /// A presigned URL to PUT one object to.
+    Put,
+    /// A policy for browser form uploads below a prefix.
+    Post,
+}
+
+pub struct UploadLinkDialog {
+    pub is_show: bool,
+    pub mode: UploadLinkMode,
+    /// The key of PUT, the key prefix of POST.
+    pub key: String,
+    pub expire: u64,
+    pub content_type: String,
+    pub min_size: String,
+    pub max_size: String,
+    pub loading: bool,
+    /// The generated URL or policy, with an example of its use.
+    pub result: String,
+    pub err: Option<String>,
+}
+
+impl Default for UploadLinkDialog {
+    fn default() -> Self {
+        Self {
+            is_show: false,
+            mode: UploadLinkMode::Put,
+            key: String::new(),
+            expire: 3600,
+            content_type: String::new(),
+            min_size: String::new(),
+            max_size: String::new(),
+            loading: false,
+            result: String::new(),
+            err: None,
+        }
+    }
+}
+
+impl UploadLinkDialog {
+    /// Keep the expiry and conditions of the last link.
+    pub fn open(&mut self, path: &str) {
+        self.is_show = true;
+        self.key = path.to_string();
+        self.loading = false;
+        self.result.clear();
+        self.err = None;
+    }
+
+    pub fn set_url(&mut self, url: String) {
+        self.loading = false;
+        self.err = None;
+        let content_type = match self.content_type.trim() {
+            "" => String::new(),
+            t => format!(" \\\n  -H 'Content-Type: {t}'"),
+        };
+        self.result = format!("{url}\n\ncurl -X PUT -T <file>{content_type} \\\n  '{url}'");
+    }
+
+    fn size_range(&self) -> Result<Option<(u64, u64)>, String> {
+        let parse = |text: &str| SearchQuery::parse_size(text).map_err(|err| err.to_string());
+        match (parse(&self.min_size)?, parse(&self.max_size)?) {
+            (None, None) => Ok(None),
+            (min, max) => {
+                let (min, max) = (min.unwrap_or(0), max.unwrap_or(5 * 1024 * 1024 * 1024));
+                if min > max {
+                    Err("The min size is larger than the max size".into())
+                } else {
+                    Ok(Some((min, max)))
+                }
+            }
+        }
+    }
+}
+
+pub fn upload_link_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.upload_link.is_show;
+    let mut generate = false;
+    let supports_post = state.client().supports_post_policy();
+    egui::Window::new("Share Upload Link")
+        .id(egui::Id::new("upload_link_dialog"))
+        .open(&mut is_show)
+        .default_width(460.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let dialog = &mut state.upload_link;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut dialog.mode, UploadLinkMode::Put, "Presigned PUT URL");
+                ui.add_enabled_ui(supports_post, |ui| {
+                    ui.radio_value(&mut dialog.mode, UploadLinkMode::Post, "POST policy")
+                        .on_disabled_hover_text("Only supported by S3 and OSS");
+                });
+            });
+            let is_post = dialog.mode == UploadLinkMode::Post;
+            egui::Grid::new("upload_link_grid")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(if is_post { "Key prefix:" } else { "Key:" });
+                    ui.add(egui::TextEdit::singleline(&mut dialog.key).desired_width(300.0))
+                        .on_hover_text(if is_post {
+                            "Uploaded keys are the prefix followed by the file name"
+                        } else {
+                            "The key of the uploaded object"
+                        });
+                    ui.end_row();
+
+                    ui.label("Expires in:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut dialog.expire)
+                                .clamp_range(1..=7 * 24 * 3600)
+                                .speed(60),
+                        );
+                        ui.label("seconds");
+                    });
+                    ui.end_row();
+
+                    ui.label("Content-Type:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut dialog.content_type)
+                            .hint_text(if is_post { "any, or image/" } else { "any" })
+                            .desired_width(300.0),
+                    );
+                    ui.end_row();
+
+                    if is_post {
+                        ui.label("Size:");
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut dialog.min_size)
+                                    .hint_text("min, e.g. 1KB")
+                                    .desired_width(100.0),
+                            );
+                            ui.label("-");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut dialog.max_size)
+                                    .hint_text("max, e.g. 10MB")
+                                    .desired_width(100.0),
+                            );
+                        });
+                        ui.end_row();
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                if ui.button(format!("{} Generate", icon::LINK)).clicked() {
+                    generate = true;
+                }
+                if dialog.loading {
+                    ui.spinner();
+                }
+                if !dialog.result.is_empty()
+                    && ui.button(format!("{} Copy", icon::CLIPBOARD)).clicked()
+                {
+                    ui.output_mut(|o| o.copied_text.clone_from(&dialog.result));
+                    global()
+                        .update_tx
+                        .send(Update::Toast(("Copied!".to_string(), ToastKind::Success)))
+                        .unwrap();
+                }
+            });
+            if let Some(err) = &dialog.err {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            if !dialog.result.is_empty() {
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut dialog.result.as_str())
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            }
+        });
+
+    if generate {
+        let dialog = &mut state.upload_link;
+        let content_type = dialog.content_type.trim().to_string();
+        match dialog.mode {
+            UploadLinkMode::Put => {
+                if dialog.key.is_empty() || dialog.key.ends_with('/') {
+                    dialog.err = Some("Enter the key of the object to upload".into());
+                } else {
+                    dialog.loading = true;
+                    let (key, expire) = (dialog.key.clone(), dialog.expire);
+                    state.presign_upload(key, expire, content_type);
+                }
+            }
+            UploadLinkMode::Post => match dialog.size_range() {
+                Ok(size_range) => {
+                    let params = PostPolicyParams {
+                        key_prefix: dialog.key.clone(),
+                        expire: dialog.expire,
+                        size_range,
+                        content_type: Some(content_type),
+                    };
+                    match state.client().post_policy(&params) {
+                        Ok(policy) => {
+                            let dialog = &mut state.upload_link;
+                            dialog.err = None;
+                            dialog.result =
+                                format!("{}\n\n{}", policy.to_json(), policy.to_curl("<file>"));
+                        }
+                        Err(err) => state.upload_link.err = Some(err.to_string()),
+                    }
+                }
+                Err(err) => dialog.err = Some(err),
+            },
+        }
+    }
+    state.upload_link.is_show = is_show;
+}
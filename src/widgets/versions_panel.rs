@@ -0,0 +1,256 @@
+use super::confirm::ConfirmAction;
+use crate::global;
+use crate::state::{State, Update};
+use cc_storage::versions::{ObjectVersion, MAX_VERSIONS};
+use cc_storage::Versioning;
+use cc_ui::icon;
+use std::collections::HashSet;
+
+/// A version loaded for a preview.
+pub struct VersionPreview {
+    pub title: String,
+    /// Key of the content in the file cache.
+    pub uri: String,
+    /// Set for images, shown from memory.
+    pub image: Option<egui::load::Bytes>,
+}
+
+#[derive(Default)]
+pub struct VersionsPanel {
+    pub is_show: bool,
+    /// Object or folder whose versions are listed.
+    pub key: String,
+    pub loading: bool,
+    pub versions: Vec<ObjectVersion>,
+    /// More versions than listed.
+    pub truncated: bool,
+    pub err: Option<String>,
+    /// `(key, version_id)` checked for deletion.
+    pub checked: HashSet<(String, String)>,
+    pub preview: Option<VersionPreview>,
+}
+
+impl VersionsPanel {
+    pub fn open(&mut self, key: String) {
+        *self = VersionsPanel {
+            is_show: true,
+            key,
+            loading: true,
+            ..Default::default()
+        };
+    }
+
+    pub fn set_versions(&mut self, versions: Vec<ObjectVersion>, truncated: bool) {
+        self.loading = false;
+        self.err = None;
+        self.checked.clear();
+        self.versions = versions;
+        self.truncated = truncated;
+    }
+
+    fn is_folder(&self) -> bool {
+        self.key.is_empty() || self.key.ends_with('/')
+    }
+}
+
+pub fn versions_panel_ui(ctx: &egui::Context, state: &mut State) {
+    let mut is_show = state.versions.is_show;
+    let title = if state.versions.key.is_empty() {
+        "Versions: /".to_string()
+    } else {
+        format!("Versions: {}", state.versions.key)
+    };
+    egui::Window::new(title)
+        .id(egui::Id::new("versions_panel"))
+        .open(&mut is_show)
+        .default_width(620.0)
+        .default_height(460.0)
+        .resizable(true)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(icon::REFRESH).on_hover_text("Refresh").clicked() {
+                    state.list_versions(state.versions.key.clone());
+                }
+                let panel = &state.versions;
+                if panel.loading {
+                    ui.spinner();
+                } else if panel.truncated {
+                    ui.label(format!("First {MAX_VERSIONS} versions"));
+                } else {
+                    ui.label(format!("{} versions", panel.versions.len()));
+                }
+                if let Some(bucket) = &state.bucket {
+                    if matches!(
+                        bucket.versioning,
+                        Versioning::Disabled | Versioning::Suspended
+                    ) {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("Versioning is {}", bucket.versioning.name()),
+                        );
+                    }
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let checked = state.versions.checked.len();
+                    ui.add_enabled_ui(checked > 0, |ui| {
+                        if ui
+                            .button(format!("{} Delete {checked}", icon::DELETE))
+                            .on_hover_text("Delete the checked versions for good")
+                            .clicked()
+                        {
+                            let items = state.versions.checked.iter().cloned().collect();
+                            global()
+                                .update_tx
+                                .send(Update::Confirm((
+                                    format!("Do you confirm to delete {checked} versions for good?"),
+                                    ConfirmAction::DeleteVersions(items),
+                                )))
+                                .unwrap();
+                        }
+                    });
+                });
+            });
+            if let Some(err) = &state.versions.err {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            ui.separator();
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Body) + 6.0;
+            let show_key = state.versions.is_folder();
+            let mut preview = None;
+            let mut download = None;
+            egui::ScrollArea::vertical()
+                .id_source("versions_list")
+                .max_height(if state.versions.preview.is_some() {
+                    200.0
+                } else {
+                    f32::INFINITY
+                })
+                .auto_shrink([false, true])
+                .show_rows(
+                    ui,
+                    row_height,
+                    state.versions.versions.len(),
+                    |ui, row_range| {
+                        let panel = &mut state.versions;
+                        for v in panel.versions[row_range].iter() {
+                            ui.horizontal(|ui| {
+                                let id = (v.key.clone(), v.version_id.clone());
+                                let mut checked = panel.checked.contains(&id);
+                                if ui.checkbox(&mut checked, "").changed() {
+                                    if checked {
+                                        panel.checked.insert(id);
+                                    } else {
+                                        panel.checked.remove(&id);
+                                    }
+                                }
+                                if show_key {
+                                    ui.add_sized(
+                                        [160.0, row_height],
+                                        egui::Label::new(global().cc_ui.text_ellipsis(&v.key, 1)),
+                                    )
+                                    .on_hover_text(&v.key);
+                                }
+                                let short: String = v.version_id.chars().take(12).collect();
+                                ui.add_sized([100.0, row_height], egui::Label::new(short))
+                                    .on_hover_text(&v.version_id);
+                                ui.label(v.date_string());
+                                ui.add_sized(
+                                    [60.0, row_height],
+                                    egui::Label::new(v.size_string()),
+                                );
+                                let badge = match (v.is_delete_marker, v.is_latest) {
+                                    (true, _) => "delete marker",
+                                    (false, true) => "latest",
+                                    _ => "",
+                                };
+                                ui.label(egui::RichText::new(badge).small().weak());
+
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if v.is_delete_marker {
+                                            return;
+                                        }
+                                        if !v.is_latest
+                                            && ui
+                                                .button(icon::BACK)
+                                                .on_hover_text("Restore as current version")
+                                                .clicked()
+                                        {
+                                            global()
+                                                .update_tx
+                                                .send(Update::Confirm((
+                                                    format!(
+                                                        "Do you confirm to restore {} to this version?",
+                                                        v.key
+                                                    ),
+                                                    ConfirmAction::RestoreVersion((
+                                                        v.key.clone(),
+                                                        v.version_id.clone(),
+                                                    )),
+                                                )))
+                                                .unwrap();
+                                        }
+                                        if ui
+                                            .button(icon::DOWNLOAD)
+                                            .on_hover_text("Download this version")
+                                            .clicked()
+                                        {
+                                            download = Some(v.clone());
+                                        }
+                                        if ui
+                                            .button(icon::ZOOM_IN)
+                                            .on_hover_text("Preview")
+                                            .clicked()
+                                        {
+                                            preview = Some(v.clone());
+                                        }
+                                    },
+                                );
+                            });
+                        }
+                    },
+                );
+            if let Some(v) = preview {
+                state.preview_version(v);
+            }
+            if let Some(v) = download {
+                state.download_version(v);
+            }
+
+            let mut close = false;
+            if let Some(preview) = &state.versions.preview {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(&preview.title);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        close = ui.button(icon::CLOSE).on_hover_text("Close").clicked();
+                    });
+                });
+                egui::ScrollArea::both()
+                    .id_source("version_preview")
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| match (&preview.image, state.file_cache.check(&preview.uri)) {
+                        (Some(bytes), _) => {
+                            ui.add(
+                                egui::Image::from_bytes(preview.uri.clone(), bytes.clone())
+                                    .shrink_to_fit(),
+                            );
+                        }
+                        (None, Some(file)) => {
+                            file.show(ui);
+                        }
+                        (None, None) => {
+                            ui.spinner();
+                        }
+                    });
+            }
+            if close {
+                state.versions.preview = None;
+            }
+        });
+
+    state.versions.is_show = is_show;
+}